@@ -34,12 +34,12 @@ impl IntoResponse for ApiError {
             ApiError::BadRequest(err) => (StatusCode::BAD_REQUEST, Some(err.to_string())),
         };
 
-        return (status_code, Json(json!({ "error": error_message }))).into_response();
+        (status_code, Json(json!({ "error": error_message }))).into_response()
     }
 }
 
 impl From<sqlx::Error> for ApiError {
     fn from(err: sqlx::Error) -> Self {
-        return ApiError::UnexpectedError(anyhow::anyhow!(err));
+        ApiError::UnexpectedError(anyhow::anyhow!(err))
     }
 }
@@ -0,0 +1,35 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::{
+    api::{AppState, error::ApiError},
+    feed_loader::is_html,
+};
+
+pub async fn get_entry(
+    State(state): State<AppState>,
+    Path(entry_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut entry = state
+        .data
+        .get_entry(&entry_id)
+        .await?
+        .ok_or(ApiError::NotFound("entry not found".to_string()))?;
+
+    // remote images go through the image proxy
+    if is_html(entry.summary_type.as_deref()) {
+        entry.summary = entry.summary.map(|html| state.proxy.rewrite_html(&html));
+    }
+    if is_html(entry.content_type.as_deref()) {
+        entry.content = entry.content.map(|html| state.proxy.rewrite_html(&html));
+    }
+    entry.extracted_content = entry
+        .extracted_content
+        .map(|html| state.proxy.rewrite_html(&html));
+
+    Ok((StatusCode::OK, Json(entry)).into_response())
+}
@@ -1,5 +1,11 @@
 mod query_entries;
 pub use query_entries::query_entries;
 
+mod get_entry;
+pub use get_entry::get_entry;
+
 mod update_read;
 pub use update_read::update_entry_read;
+
+mod update_playback;
+pub use update_playback::update_entry_playback;
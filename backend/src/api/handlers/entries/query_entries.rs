@@ -18,6 +18,8 @@ pub struct QueryEntriesQuery {
     limit: Option<u64>,
     query: Option<String>,
     feed_id: Option<String>,
+    author: Option<String>,
+    category: Option<String>,
     unread: Option<bool>,
     starred: Option<bool>,
     start: Option<DateTime<Utc>>,
@@ -31,15 +33,15 @@ pub async fn query_entries(
 ) -> Result<impl IntoResponse, ApiError> {
     let cursor = if let Some(left) = query.left {
         Some(Cursor::Left(left))
-    } else if let Some(right) = query.right {
-        Some(Cursor::Right(right))
     } else {
-        None
+        query.right.map(Cursor::Right)
     };
 
     let has_filters = query.limit.is_some()
         || query.query.is_some()
         || query.feed_id.is_some()
+        || query.author.is_some()
+        || query.category.is_some()
         || query.unread.is_some()
         || query.starred.is_some()
         || query.start.is_some()
@@ -51,6 +53,8 @@ pub async fn query_entries(
             limit: query.limit,
             query: query.query,
             feed_id: query.feed_id,
+            author: query.author,
+            category: query.category,
             unread: query.unread,
             starred: query.starred,
             start: query.start,
@@ -0,0 +1,37 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use reqwest::StatusCode;
+
+use crate::api::{AppState, error::ApiError};
+
+#[derive(serde::Deserialize)]
+pub struct UpdateEntryPlaybackBody {
+    /// `null` clears the position, e.g. once an episode has been finished.
+    pub position_seconds: Option<i32>,
+}
+
+pub async fn update_entry_playback(
+    State(state): State<AppState>,
+    Path(entry_id): Path<String>,
+    Json(body): Json<UpdateEntryPlaybackBody>,
+) -> Result<impl IntoResponse, ApiError> {
+    if body.position_seconds.is_some_and(|position| position < 0) {
+        return Err(ApiError::BadRequest(
+            "position_seconds must not be negative".to_string(),
+        ));
+    }
+
+    let updated = state
+        .data
+        .update_entry_playback_position(&entry_id, body.position_seconds)
+        .await?;
+
+    if !updated {
+        return Err(ApiError::NotFound("entry not found".to_string()));
+    }
+
+    Ok((StatusCode::OK, Json(serde_json::json!({"success": true}))).into_response())
+}
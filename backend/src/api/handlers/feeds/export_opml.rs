@@ -10,7 +10,7 @@ pub async fn export_opml(State(state): State<AppState>) -> Result<impl IntoRespo
         .await
         .map_err(|err| ApiError::UnexpectedError(err.into()))?;
 
-    let opml = generate_opml(&feeds).map_err(|err| ApiError::UnexpectedError(err.into()))?;
+    let opml = generate_opml(&feeds).map_err(ApiError::UnexpectedError)?;
 
     Ok((
         StatusCode::OK,
@@ -24,10 +24,8 @@ pub async fn get_feed_entries(
 ) -> Result<impl IntoResponse, ApiError> {
     let cursor = if let Some(left) = input.left {
         Some(Cursor::Left(left))
-    } else if let Some(right) = input.right {
-        Some(Cursor::Right(right))
     } else {
-        None
+        input.right.map(Cursor::Right)
     };
 
     let limit = input.limit;
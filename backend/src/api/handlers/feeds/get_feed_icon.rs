@@ -10,9 +10,14 @@ use serde_json::json;
 
 use crate::api::{AppState, error::ApiError};
 
+/// Icons change rarely, and the ETag lets clients revalidate cheaply once this
+/// runs out.
+const ICON_CACHE_CONTROL: &str = "private, max-age=86400";
+
 pub async fn get_feed_icon(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    request_headers: HeaderMap,
 ) -> Result<impl IntoResponse, ApiError> {
     let icon = state
         .data
@@ -21,20 +26,66 @@ pub async fn get_feed_icon(
         .context("error getting feed")?;
 
     if let Some(icon) = icon {
+        let etag = format!("\"{}\"", icon.hash);
+        let mut headers = HeaderMap::new();
+        headers.append(
+            header::ETAG,
+            etag.parse::<HeaderValue>().context("invalid icon hash")?,
+        );
+        headers.append(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(ICON_CACHE_CONTROL),
+        );
+
+        if matches_etag(&request_headers, &etag) {
+            return Ok((StatusCode::NOT_MODIFIED, headers).into_response());
+        }
+
         let content_type = icon.content_type.parse::<HeaderValue>().unwrap();
         let data = if content_type == "image/svg+xml" {
             Body::from(String::from_utf8_lossy(&icon.data).to_string())
         } else {
             Body::from(icon.data)
         };
-        let mut headers = HeaderMap::new();
         headers.append(header::CONTENT_TYPE, content_type);
         return Ok((headers, data).into_response());
     }
 
-    return Ok((
+    Ok((
         StatusCode::NOT_FOUND,
         Json(json!({ "status": "not_found" })),
     )
-        .into_response());
+        .into_response())
+}
+
+/// Whether `If-None-Match` lists `etag`, compared weakly as RFC 9110 asks for.
+fn matches_etag(request_headers: &HeaderMap, etag: &str) -> bool {
+    request_headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn if_none_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn matches_if_none_match() {
+        assert!(matches_etag(&if_none_match("\"abc\""), "\"abc\""));
+        assert!(matches_etag(&if_none_match("W/\"abc\""), "\"abc\""));
+        assert!(matches_etag(&if_none_match("\"x\", \"abc\""), "\"abc\""));
+        assert!(matches_etag(&if_none_match("*"), "\"abc\""));
+        assert!(!matches_etag(&if_none_match("\"abcd\""), "\"abc\""));
+        assert!(!matches_etag(&HeaderMap::new(), "\"abc\""));
+    }
 }
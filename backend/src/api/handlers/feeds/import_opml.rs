@@ -17,7 +17,7 @@ use url::Url;
 
 use crate::{
     api::{AppState, error::ApiError},
-    feed_loader::{self, FeedResult},
+    feed_loader::{self, FeedResult, LoadOptions},
 };
 
 const MAX_OPML_BYTES: usize = 5 * 1024 * 1024;
@@ -158,7 +158,7 @@ async fn run_import_job(data: crate::db::Data, job_id: String, feed_urls: Vec<St
     }
 
     let job_id_clone = job_id.clone();
-    stream::iter(feed_urls)
+    stream::iter(feed_loader::interleave_hosts(feed_urls, |url| url))
         .for_each_concurrent(5, |url| {
             let data = data.clone();
             let job_id = job_id_clone.clone();
@@ -170,7 +170,7 @@ async fn run_import_job(data: crate::db::Data, job_id: String, feed_urls: Vec<St
                     error!("error updating opml import item: {err:#}");
                 }
 
-                match feed_loader::load_feed(&url, None, None).await {
+                match feed_loader::load_feed(&url, None, None, LoadOptions::default()).await {
                     Ok(FeedResult::Loaded(loaded_feed)) => {
                         if let Err(err) = data
                             .update_feed_headers(
@@ -220,7 +220,7 @@ async fn run_import_job(data: crate::db::Data, job_id: String, feed_urls: Vec<St
                         )
                         .await;
                     }
-                    Ok(FeedResult::NotModified) => {
+                    Ok(FeedResult::NotModified { .. }) => {
                         mark_import_failure(&data, &job_id, &url, "not_modified".to_string()).await;
                     }
                     Ok(FeedResult::NotFound) => {
@@ -322,17 +322,17 @@ fn extract_opml_feed_urls(bytes: &[u8]) -> Result<Vec<String>, ApiError> {
 
     loop {
         match reader.read_event_into(&mut buf) {
-            Ok(XmlEvent::Start(event)) | Ok(XmlEvent::Empty(event)) => {
-                if event.name().as_ref() == b"outline" {
-                    for attr in event.attributes().with_checks(false) {
-                        let attr = attr.map_err(|err| ApiError::BadRequest(err.to_string()))?;
-                        if attr.key.as_ref() == b"xmlUrl" {
-                            let value = attr
-                                .unescape_value()
-                                .map_err(|err| ApiError::BadRequest(err.to_string()))?;
-                            if let Some(url) = normalize_url(value.as_ref()) {
-                                urls.push(url);
-                            }
+            Ok(XmlEvent::Start(event)) | Ok(XmlEvent::Empty(event))
+                if event.name().as_ref() == b"outline" =>
+            {
+                for attr in event.attributes().with_checks(false) {
+                    let attr = attr.map_err(|err| ApiError::BadRequest(err.to_string()))?;
+                    if attr.key.as_ref() == b"xmlUrl" {
+                        let value = attr
+                            .unescape_value()
+                            .map_err(|err| ApiError::BadRequest(err.to_string()))?;
+                        if let Some(url) = normalize_url(value.as_ref()) {
+                            urls.push(url);
                         }
                     }
                 }
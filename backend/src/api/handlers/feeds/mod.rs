@@ -4,6 +4,12 @@ pub use get_feed_icon::get_feed_icon;
 mod new_feed;
 pub use new_feed::new_feed;
 
+mod preview_feed;
+pub use preview_feed::preview_feed;
+
+mod subscribe_feed;
+pub use subscribe_feed::subscribe_feed;
+
 mod import_opml;
 pub use import_opml::{import_opml, import_opml_events};
 
@@ -27,3 +33,23 @@ pub use get_feed_entries::get_feed_entries;
 
 mod sync_feed;
 pub use sync_feed::sync_feed;
+
+use crate::{
+    api::error::ApiError,
+    feed_loader::{FeedError, FeedResult, FetchError},
+};
+
+/// Errors that have a status of their own become that status, problems with
+/// what the user asked for become bad requests.
+fn feed_result_for_error(err: FeedError) -> Result<FeedResult, ApiError> {
+    match err {
+        FeedError::NotFound | FeedError::Gone => Ok(FeedResult::NotFound),
+        FeedError::Fetch(FetchError::Disallowed) => Ok(FeedResult::Disallowed),
+        FeedError::InvalidUrl
+        | FeedError::Parse
+        | FeedError::UnexpectedHtml
+        | FeedError::Html(_)
+        | FeedError::Fetch(FetchError::InvalidUrl) => Err(ApiError::BadRequest(err.to_string())),
+        err => Err(anyhow::Error::new(err).into()),
+    }
+}
@@ -8,23 +8,37 @@ use axum::{
 use serde_json::json;
 
 use crate::{
-    api::{AppState, error::ApiError},
-    feed_loader::{self, FeedResult},
+    api::{AppState, error::ApiError, handlers::feeds::feed_result_for_error},
+    feed_loader::{self, FeedResult, LoadOptions},
 };
 
 #[derive(Debug, serde::Deserialize)]
 pub struct AddFeedQuery {
     url: String,
     force_similar_feed: Option<bool>,
+    /// Adds the feed even if robots.txt disallows it, and keeps ignoring
+    /// robots.txt when syncing it.
+    ignore_robots: Option<bool>,
 }
 
 pub async fn new_feed(
     State(state): State<AppState>,
     Query(query): Query<AddFeedQuery>,
 ) -> Result<impl IntoResponse, ApiError> {
-    let res = feed_loader::load_feed(&query.url, None, None)
-        .await
-        .unwrap();
+    let ignore_robots = query.ignore_robots.unwrap_or(false);
+    let options = LoadOptions {
+        ignore_robots,
+        ..Default::default()
+    };
+
+    // a preview the user just looked at is what they expect to subscribe to
+    let res = match state.previews.take(query.url.trim()) {
+        Some(loaded_feed) => FeedResult::Loaded(Box::new(loaded_feed)),
+        None => match feed_loader::load_feed(&query.url, None, None, options).await {
+            Ok(res) => res,
+            Err(err) => feed_result_for_error(err)?,
+        },
+    };
     let force_similar = query.force_similar_feed.unwrap_or(false);
 
     let existing_feed = if !force_similar {
@@ -38,11 +52,12 @@ pub async fn new_feed(
     };
 
     let response = match res {
-        FeedResult::NeedsChoice(feed_urls) => (
+        FeedResult::NeedsChoice(candidates) => (
             StatusCode::OK,
             Json(json!({
                 "status": "discovered_multiple",
-                "feed_urls": feed_urls,
+                "feed_urls": candidates.iter().map(|c| &c.url).collect::<Vec<_>>(),
+                "feeds": candidates,
                 "similar_feed_url": existing_feed.map(|f| f.feed_url)
             })),
         )
@@ -82,11 +97,18 @@ pub async fn new_feed(
                     )
                     .await?;
 
+                if ignore_robots {
+                    state
+                        .data
+                        .set_feed_ignore_robots(&loaded_feed.feed.feed_url, true)
+                        .await?;
+                }
+
                 (StatusCode::OK, Json(json!({ "status": "feed_added" }))).into_response()
             }
         }
 
-        FeedResult::NotModified => (
+        FeedResult::NotModified { .. } => (
             StatusCode::NOT_MODIFIED,
             Json(json!({ "status": "not_modified" })),
         )
@@ -0,0 +1,123 @@
+use std::cmp::Reverse;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde_json::json;
+
+use crate::{
+    api::{AppState, error::ApiError, handlers::feeds::feed_result_for_error},
+    db::IconLookup,
+    feed_loader::{self, FeedResult, LoadOptions, is_html},
+};
+
+const DEFAULT_ENTRY_LIMIT: usize = 10;
+const MAX_ENTRY_LIMIT: usize = 50;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PreviewFeedQuery {
+    url: String,
+    /// How many of the latest entries to return.
+    limit: Option<usize>,
+    /// Loads the feed even if robots.txt disallows it.
+    ignore_robots: Option<bool>,
+}
+
+/// Loads a feed the way adding it would, without storing anything. The result
+/// is kept for a while so that subscribing to the same URL reuses it.
+pub async fn preview_feed(
+    State(state): State<AppState>,
+    Query(query): Query<PreviewFeedQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let url = query.url.trim();
+    if url.is_empty() {
+        return Err(ApiError::BadRequest("url is required".to_string()));
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_ENTRY_LIMIT)
+        .min(MAX_ENTRY_LIMIT);
+
+    let options = LoadOptions {
+        ignore_robots: query.ignore_robots.unwrap_or(false),
+        ..Default::default()
+    };
+    let res = match feed_loader::load_feed(url, None, None, options).await {
+        Ok(res) => res,
+        Err(err) => feed_result_for_error(err)?,
+    };
+
+    let response = match res {
+        FeedResult::Loaded(loaded_feed) => {
+            let mut entries: Vec<_> = loaded_feed.entries.iter().collect();
+            entries.sort_by_key(|entry| Reverse(entry.published_at));
+            let entries: Vec<_> = entries
+                .into_iter()
+                .take(limit)
+                .cloned()
+                .map(|mut entry| {
+                    // remote images go through the image proxy
+                    if is_html(entry.summary_type.as_deref()) {
+                        entry.summary = entry.summary.map(|html| state.proxy.rewrite_html(&html));
+                    }
+                    if is_html(entry.content_type.as_deref()) {
+                        entry.content = entry.content.map(|html| state.proxy.rewrite_html(&html));
+                    }
+                    entry
+                })
+                .collect();
+
+            let body = json!({
+                "status": "preview",
+                "feed": loaded_feed.feed,
+                "icon": match &loaded_feed.icon {
+                    IconLookup::Found(icon) => Some(format!(
+                        "data:{};base64,{}",
+                        icon.content_type,
+                        STANDARD.encode(&icon.data)
+                    )),
+                    IconLookup::NotFound | IconLookup::Skipped => None,
+                },
+                "entries": entries,
+                "entry_count": loaded_feed.entries.len(),
+            });
+            state.previews.insert(url, *loaded_feed);
+
+            (StatusCode::OK, Json(body)).into_response()
+        }
+
+        FeedResult::NeedsChoice(candidates) => (
+            StatusCode::OK,
+            Json(json!({
+                "status": "discovered_multiple",
+                "feed_urls": candidates.iter().map(|c| &c.url).collect::<Vec<_>>(),
+                "feeds": candidates,
+            })),
+        )
+            .into_response(),
+
+        FeedResult::NotModified { .. } => (
+            StatusCode::NOT_MODIFIED,
+            Json(json!({ "status": "not_modified" })),
+        )
+            .into_response(),
+
+        FeedResult::NotFound => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "status": "not_found" })),
+        )
+            .into_response(),
+
+        FeedResult::Disallowed => (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "status": "not_allowed" })),
+        )
+            .into_response(),
+    };
+
+    Ok(response)
+}
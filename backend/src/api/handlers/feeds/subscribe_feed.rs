@@ -0,0 +1,88 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde_json::json;
+
+use crate::{
+    api::{AppState, error::ApiError, handlers::feeds::feed_result_for_error},
+    feed_loader::{self, FeedResult, LoadOptions},
+};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct SubscribeFeedBody {
+    /// One of the `feeds` returned when adding a page found several.
+    feed_url: String,
+    /// Subscribes even if robots.txt disallows the feed, and keeps ignoring
+    /// robots.txt when syncing it.
+    #[serde(default)]
+    ignore_robots: bool,
+}
+
+/// Subscribes to a feed the user picked, without running discovery again.
+pub async fn subscribe_feed(
+    State(state): State<AppState>,
+    Json(payload): Json<SubscribeFeedBody>,
+) -> Result<impl IntoResponse, ApiError> {
+    let feed_url = payload.feed_url.trim();
+    if feed_url.is_empty() {
+        return Err(ApiError::BadRequest("feed_url is required".to_string()));
+    }
+
+    let res = match state.previews.take(feed_url) {
+        Some(loaded_feed) => FeedResult::Loaded(Box::new(loaded_feed)),
+        None => {
+            let options = LoadOptions {
+                ignore_robots: payload.ignore_robots,
+                ..Default::default()
+            };
+            match feed_loader::load_selected_feed(feed_url, None, None, options).await {
+                Ok(loaded_feed) => FeedResult::Loaded(Box::new(loaded_feed)),
+                Err(err) => feed_result_for_error(err)?,
+            }
+        }
+    };
+
+    let loaded_feed = match res {
+        FeedResult::Loaded(loaded_feed) => loaded_feed,
+        FeedResult::Disallowed => {
+            return Ok((
+                StatusCode::FORBIDDEN,
+                Json(json!({ "status": "not_allowed" })),
+            )
+                .into_response());
+        }
+        // a selected feed is fetched without cache headers and never
+        // discovered from, so anything else means there's no feed there
+        FeedResult::NotFound | FeedResult::NotModified { .. } | FeedResult::NeedsChoice(_) => {
+            return Ok((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "status": "not_found" })),
+            )
+                .into_response());
+        }
+    };
+
+    if let Err(e) = state
+        .data
+        .update_feed_headers(
+            &loaded_feed.feed.feed_url,
+            loaded_feed.http_etag.as_deref(),
+            loaded_feed.http_last_modified.as_deref(),
+        )
+        .await
+    {
+        tracing::error!("error updating feed headers: {e:#}");
+    }
+
+    state
+        .data
+        .upsert_feed_and_entries_and_icon(&loaded_feed.feed, loaded_feed.entries, loaded_feed.icon)
+        .await?;
+
+    if payload.ignore_robots {
+        state
+            .data
+            .set_feed_ignore_robots(&loaded_feed.feed.feed_url, true)
+            .await?;
+    }
+
+    Ok((StatusCode::OK, Json(json!({ "status": "feed_added" }))).into_response())
+}
@@ -9,7 +9,8 @@ use axum::{
 use crate::{
     api::{AppState, error::ApiError},
     feed_loader::{
-        FeedResult, SYNC_RESULT_NOT_MODIFIED, load_feed, sync_result_for_error,
+        FeedResult, LoadOptions, SYNC_RESULT_NOT_MODIFIED, extract_full_content, follow_feed_move,
+        follow_loaded_feed_move, icon_needs_refresh, load_feed, next_sync, sync_result_for_error,
         sync_result_for_feed_result,
     },
 };
@@ -25,15 +26,36 @@ pub async fn sync_feed(
         .context("error getting feed to sync")?
         .ok_or(ApiError::NotFound("feed not found".to_string()))?;
 
-    let feed_res = load_feed(&feed.feed_url, None, None).await;
+    let feed_res = load_feed(
+        &feed.feed_url,
+        None,
+        None,
+        LoadOptions {
+            load_icon: icon_needs_refresh(feed.icon_refreshed_at),
+            ignore_robots: feed.ignore_robots,
+        },
+    )
+    .await;
+
+    let next = next_sync(&feed, &feed_res, &state.sync_bounds);
+    if let Err(e) = state
+        .data
+        .schedule_feed_sync(&feed.feed_url, next.interval, next.delay)
+        .await
+    {
+        tracing::error!("error scheduling next sync: {e:#}");
+    }
 
     match feed_res {
-        Ok(FeedResult::Loaded(loaded_feed)) => {
+        Ok(FeedResult::Loaded(mut loaded_feed)) => {
+            let feed_url =
+                follow_loaded_feed_move(&state.data, &feed.feed_url, &mut loaded_feed).await;
+
             // Store the new cache headers
             if let Err(e) = state
                 .data
                 .update_feed_headers(
-                    &feed.feed_url,
+                    &feed_url,
                     loaded_feed.http_etag.as_deref(),
                     loaded_feed.http_last_modified.as_deref(),
                 )
@@ -51,6 +73,10 @@ pub async fn sync_feed(
                 )
                 .await?;
 
+            if feed.fetch_full_content {
+                extract_full_content(&state.data, &feed_id, feed.ignore_robots).await;
+            }
+
             let feed = state
                 .data
                 .get_feed_by_id_with_entry_counts(&feed_id)
@@ -59,10 +85,11 @@ pub async fn sync_feed(
 
             Ok((StatusCode::OK, Json(feed)))
         }
-        Ok(FeedResult::NotModified) => {
+        Ok(FeedResult::NotModified { moved_to }) => {
+            let feed_url = follow_feed_move(&state.data, &feed.feed_url, moved_to.as_deref()).await;
             state
                 .data
-                .set_feed_sync_result(&feed.feed_url, SYNC_RESULT_NOT_MODIFIED)
+                .set_feed_sync_result(&feed_url, SYNC_RESULT_NOT_MODIFIED)
                 .await?;
 
             let feed = state
@@ -12,6 +12,12 @@ pub struct UpdateFeedBody {
     title: String,
     feed_url: String,
     site_url: Option<String>,
+    /// Extract each entry's article from its page, for feeds that only ship
+    /// a teaser. Left unchanged when absent.
+    fetch_full_content: Option<bool>,
+    /// Fetch the feed even where robots.txt disallows it. Left unchanged when
+    /// absent.
+    ignore_robots: Option<bool>,
 }
 
 pub async fn update_feed(
@@ -37,6 +43,20 @@ pub async fn update_feed(
         )
         .await?;
 
+    if let Some(fetch_full_content) = payload.fetch_full_content {
+        state
+            .data
+            .set_feed_fetch_full_content(&feed_id, fetch_full_content)
+            .await?;
+    }
+
+    if let Some(ignore_robots) = payload.ignore_robots {
+        state
+            .data
+            .set_feed_ignore_robots(feed_url, ignore_robots)
+            .await?;
+    }
+
     let updated_feed = state
         .data
         .get_feed_by_id_with_entry_counts(&feed_id)
@@ -1,2 +1,3 @@
 pub mod entries;
 pub mod feeds;
+pub mod proxy;
@@ -0,0 +1,89 @@
+use anyhow::Context;
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::IntoResponse,
+};
+use chrono::Utc;
+
+use crate::{
+    api::{AppState, error::ApiError},
+    db::NewProxiedImage,
+    feed_loader::fetch_image,
+};
+
+/// Cached images are refetched after this long.
+const CACHE_TTL_DAYS: i64 = 7;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ProxyParams {
+    url: String,
+    sig: String,
+}
+
+pub async fn get_proxied_image(
+    State(state): State<AppState>,
+    Query(params): Query<ProxyParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !state.proxy.verify(&params.url, &params.sig) {
+        return Err(ApiError::BadRequest("invalid signature".to_string()));
+    }
+
+    let cached = state
+        .data
+        .get_proxied_image(&params.url)
+        .await
+        .context("error getting cached image")?;
+
+    let (content_type, data) = match cached {
+        Some(cached) if Utc::now() - cached.fetched_at < chrono::Duration::days(CACHE_TTL_DAYS) => {
+            (cached.content_type, cached.data)
+        }
+        cached => match fetch_image(&params.url).await {
+            Ok(image) => {
+                let image = NewProxiedImage {
+                    url: params.url,
+                    content_type: image.content_type,
+                    data: image.data,
+                };
+                if let Err(e) = state.data.upsert_proxied_image(&image).await {
+                    tracing::error!("error caching image: {e:#}");
+                }
+                (image.content_type, image.data)
+            }
+            // a stale copy beats a broken image
+            Err(err) => match cached {
+                Some(cached) => {
+                    tracing::debug!("serving stale image: {err}");
+                    (cached.content_type, cached.data)
+                }
+                None => {
+                    tracing::debug!("error fetching image: {err}");
+                    return Ok(StatusCode::BAD_GATEWAY.into_response());
+                }
+            },
+        },
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        content_type
+            .parse::<HeaderValue>()
+            .context("invalid content type")?,
+    );
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("private, max-age=604800"),
+    );
+    headers.insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("default-src 'none'"),
+    );
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+
+    Ok((headers, data).into_response())
+}
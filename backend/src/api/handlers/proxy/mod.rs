@@ -0,0 +1,2 @@
+mod get_proxied_image;
+pub use get_proxied_image::get_proxied_image;
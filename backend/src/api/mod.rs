@@ -1,33 +1,58 @@
 use axum::{
     Router,
-    routing::{get, post},
+    routing::{get, post, put},
 };
 use tokio::{net::TcpListener, sync::watch};
 
-use crate::db::Data;
+use crate::{
+    api::{
+        preview::{PREVIEW_TTL, PreviewCache},
+        proxy::{ImageProxy, prune_image_cache_loop},
+    },
+    db::Data,
+    feed_loader::SyncBounds,
+};
 
 mod error;
 mod frontend;
 mod handlers;
+mod preview;
+mod proxy;
 
 #[derive(Clone)]
 struct AppState {
     data: Data,
+    proxy: ImageProxy,
+    previews: PreviewCache,
+    sync_bounds: SyncBounds,
 }
 
 pub struct ApiConfig {
     pub host: String,
     pub frontend_dir: Option<String>,
+    pub proxy_secret: Option<String>,
+    pub sync_bounds: SyncBounds,
 }
 
 pub async fn start_api(data: Data, config: ApiConfig, mut shutdown_rx: watch::Receiver<bool>) {
-    let state = AppState { data };
+    let state = AppState {
+        data,
+        proxy: ImageProxy::new(config.proxy_secret.as_deref()),
+        previews: PreviewCache::new(PREVIEW_TTL),
+        sync_bounds: config.sync_bounds,
+    };
+    tokio::spawn(prune_image_cache_loop(
+        state.data.clone(),
+        shutdown_rx.clone(),
+    ));
 
     let v1_routes = Router::new()
         .route(
             "/feeds",
             post(handlers::feeds::new_feed).get(handlers::feeds::query_feeds),
         )
+        .route("/feeds/preview", get(handlers::feeds::preview_feed))
+        .route("/feeds/subscribe", post(handlers::feeds::subscribe_feed))
         .route("/feeds/import", post(handlers::feeds::import_opml))
         .route("/feeds/export", get(handlers::feeds::export_opml))
         .route(
@@ -47,10 +72,16 @@ pub async fn start_api(data: Data, config: ApiConfig, mut shutdown_rx: watch::Re
         )
         .route("/feeds/{id}/sync", post(handlers::feeds::sync_feed))
         .route("/entries", get(handlers::entries::query_entries))
+        .route("/entries/{id}", get(handlers::entries::get_entry))
         .route(
             "/entries/{id}/read",
             post(handlers::entries::update_entry_read),
         )
+        .route(
+            "/entries/{id}/playback",
+            put(handlers::entries::update_entry_playback),
+        )
+        .route("/proxy", get(handlers::proxy::get_proxied_image))
         .with_state(state);
 
     let mut app = Router::new().nest(
@@ -0,0 +1,86 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::feed_loader::LoadedFeed;
+
+/// How long a previewed feed is reused when subscribing to it.
+pub const PREVIEW_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Feeds loaded for a preview, keyed by the URL they were requested with, so
+/// that subscribing right after doesn't fetch everything again.
+#[derive(Clone)]
+pub struct PreviewCache {
+    ttl: Duration,
+    previews: Arc<Mutex<HashMap<String, (Instant, LoadedFeed)>>>,
+}
+
+impl PreviewCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            previews: Arc::default(),
+        }
+    }
+
+    pub fn insert(&self, url: &str, loaded: LoadedFeed) {
+        let mut previews = self.previews.lock().expect("preview cache lock poisoned");
+        previews.retain(|_, (loaded_at, _)| loaded_at.elapsed() < self.ttl);
+        previews.insert(url.to_owned(), (Instant::now(), loaded));
+    }
+
+    /// The preview of `url`, if it is still fresh. It is removed either way.
+    pub fn take(&self, url: &str) -> Option<LoadedFeed> {
+        let mut previews = self.previews.lock().expect("preview cache lock poisoned");
+        previews
+            .remove(url)
+            .filter(|(loaded_at, _)| loaded_at.elapsed() < self.ttl)
+            .map(|(_, loaded)| loaded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{IconLookup, NewFeed};
+
+    fn loaded(title: &str) -> LoadedFeed {
+        LoadedFeed {
+            feed: NewFeed {
+                title: title.to_string(),
+                site_url: None,
+                feed_url: "https://example.com/feed".to_string(),
+            },
+            entries: Vec::new(),
+            icon: IconLookup::Skipped,
+            http_etag: None,
+            http_last_modified: None,
+            update_interval: None,
+            freshness: None,
+            moved_to: None,
+        }
+    }
+
+    #[test]
+    fn takes_fresh_previews_once() {
+        let cache = PreviewCache::new(PREVIEW_TTL);
+        cache.insert("example.com", loaded("Example"));
+
+        assert_eq!(
+            cache.take("example.com").map(|l| l.feed.title).as_deref(),
+            Some("Example")
+        );
+        assert!(cache.take("example.com").is_none());
+        assert!(cache.take("other.example.com").is_none());
+    }
+
+    #[test]
+    fn drops_stale_previews() {
+        let cache = PreviewCache::new(Duration::ZERO);
+        cache.insert("example.com", loaded("Example"));
+
+        assert!(cache.take("example.com").is_none());
+    }
+}
@@ -0,0 +1,246 @@
+use std::{sync::Arc, time::Duration};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use hmac::{Hmac, Mac};
+use html5ever::{
+    ParseOpts, QualName, local_name, ns, parse_fragment,
+    serialize::{SerializeOpts, TraversalScope, serialize},
+    tendril::TendrilSink,
+};
+use markup5ever_rcdom::{Handle, NodeData, RcDom, SerializableHandle};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use sha2::Sha256;
+use tokio::sync::watch;
+
+use crate::db::Data;
+
+/// Cached images that haven't been fetched for this long are deleted; images
+/// still in use are refetched well before.
+const IMAGE_CACHE_MAX_AGE: chrono::Duration = chrono::Duration::days(30);
+/// Beyond this, the least recently fetched images are deleted.
+const IMAGE_CACHE_MAX_BYTES: i64 = 512 * 1024 * 1024;
+const IMAGE_CACHE_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Signs the URLs of the image proxy, so that it only fetches images we
+/// handed out links for rather than acting as an open proxy.
+#[derive(Clone)]
+pub struct ImageProxy {
+    key: Arc<[u8]>,
+}
+
+impl ImageProxy {
+    /// Without a configured secret, a random one is used, and proxied URLs
+    /// stop working when the server restarts.
+    pub fn new(secret: Option<&str>) -> Self {
+        let key: Arc<[u8]> = match secret {
+            Some(secret) => secret.as_bytes().into(),
+            None => {
+                tracing::warn!("PROXY_SECRET is not set, using a random secret");
+                rand::random::<[u8; 32]>().into()
+            }
+        };
+        Self { key }
+    }
+
+    pub fn sign(&self, url: &str) -> String {
+        URL_SAFE_NO_PAD.encode(self.mac(url).finalize().into_bytes())
+    }
+
+    pub fn verify(&self, url: &str, signature: &str) -> bool {
+        URL_SAFE_NO_PAD
+            .decode(signature)
+            .is_ok_and(|signature| self.mac(url).verify_slice(&signature).is_ok())
+    }
+
+    /// Where clients can load the image at `url` from.
+    pub fn proxied_url(&self, url: &str) -> String {
+        format!(
+            "/api/v1/proxy?url={}&sig={}",
+            utf8_percent_encode(url, NON_ALPHANUMERIC),
+            self.sign(url)
+        )
+    }
+
+    /// Points the images of entry HTML at the proxy. `data:` images and
+    /// anything that isn't an absolute http(s) URL are left alone.
+    pub fn rewrite_html(&self, html: &str) -> String {
+        let dom = parse_fragment(
+            RcDom::default(),
+            ParseOpts::default(),
+            QualName::new(None, ns!(html), local_name!("body")),
+            Vec::new(),
+            false,
+        )
+        .one(html);
+
+        // the fragment parser puts everything inside an <html> element
+        let Some(root) = dom.document.children.borrow().first().cloned() else {
+            return html.to_owned();
+        };
+        self.rewrite_node(&root);
+
+        let mut bytes = Vec::new();
+        let serialized = serialize(
+            &mut bytes,
+            &SerializableHandle::from(root),
+            SerializeOpts {
+                traversal_scope: TraversalScope::ChildrenOnly(None),
+                ..Default::default()
+            },
+        );
+        match serialized.ok().and_then(|_| String::from_utf8(bytes).ok()) {
+            Some(rewritten) => rewritten,
+            None => html.to_owned(),
+        }
+    }
+
+    fn rewrite_node(&self, node: &Handle) {
+        if let NodeData::Element { name, attrs, .. } = &node.data {
+            let element = name.local.as_ref();
+            for attr in attrs.borrow_mut().iter_mut() {
+                let rewritten = match (element, attr.name.local.as_ref()) {
+                    ("img", "src") | ("video", "poster") => self.rewrite_url(&attr.value),
+                    ("img" | "source", "srcset") => self.rewrite_srcset(&attr.value),
+                    _ => None,
+                };
+                if let Some(rewritten) = rewritten {
+                    attr.value = rewritten.into();
+                }
+            }
+        }
+
+        for child in node.children.borrow().iter() {
+            self.rewrite_node(child);
+        }
+    }
+
+    fn rewrite_url(&self, url: &str) -> Option<String> {
+        let url = url.trim();
+        let is_remote = url.starts_with("https://") || url.starts_with("http://");
+        is_remote.then(|| self.proxied_url(url))
+    }
+
+    fn rewrite_srcset(&self, srcset: &str) -> Option<String> {
+        let candidates: Vec<String> = srcset
+            .split(',')
+            .filter_map(|candidate| {
+                let mut parts = candidate.split_whitespace();
+                let url = parts.next()?;
+                let url = self.rewrite_url(url).unwrap_or_else(|| url.to_owned());
+                Some(
+                    std::iter::once(url)
+                        .chain(parts.map(str::to_owned))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+            })
+            .collect();
+        Some(candidates.join(", "))
+    }
+
+    fn mac(&self, url: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("hmac accepts keys of any size");
+        mac.update(url.as_bytes());
+        mac
+    }
+}
+
+/// Keeps the image cache from growing forever, pruning it every
+/// `IMAGE_CACHE_PRUNE_INTERVAL` until shutdown.
+pub async fn prune_image_cache_loop(data: Data, mut shutdown_rx: watch::Receiver<bool>) {
+    loop {
+        match data
+            .prune_proxied_images(
+                chrono::Utc::now() - IMAGE_CACHE_MAX_AGE,
+                IMAGE_CACHE_MAX_BYTES,
+            )
+            .await
+        {
+            Ok(0) => {}
+            Ok(deleted) => tracing::info!(deleted, "pruned image cache"),
+            Err(e) => tracing::error!("error pruning image cache: {e:#}"),
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(IMAGE_CACHE_PRUNE_INTERVAL) => {}
+            _ = shutdown_rx.wait_for(|&v| v) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy() -> ImageProxy {
+        ImageProxy::new(Some("test secret"))
+    }
+
+    #[test]
+    fn verifies_own_signatures_only() {
+        let proxy = proxy();
+        let url = "https://images.example.com/a.png";
+        let signature = proxy.sign(url);
+
+        assert!(proxy.verify(url, &signature));
+        assert!(!proxy.verify("https://images.example.com/b.png", &signature));
+        assert!(!proxy.verify(url, "not a signature"));
+        assert!(!ImageProxy::new(Some("other secret")).verify(url, &signature));
+    }
+
+    #[test]
+    fn encodes_proxied_urls() {
+        let proxy = proxy();
+        let url = "https://images.example.com/a.png?size=2&fmt=webp";
+
+        assert_eq!(
+            proxy.proxied_url(url),
+            format!(
+                "/api/v1/proxy?url=https%3A%2F%2Fimages%2Eexample%2Ecom%2Fa%2Epng%3Fsize%3D2%26fmt%3Dwebp&sig={}",
+                proxy.sign(url)
+            )
+        );
+    }
+
+    #[test]
+    fn rewrites_images() {
+        let proxy = proxy();
+        let a = proxy
+            .proxied_url("https://images.example.com/a.png")
+            .replace('&', "&amp;");
+        let b = proxy
+            .proxied_url("https://images.example.com/b.png")
+            .replace('&', "&amp;");
+
+        assert_eq!(
+            proxy.rewrite_html(
+                "<p>Hi <img src=\"https://images.example.com/a.png\" srcset=\"https://images.example.com/a.png 1x, https://images.example.com/b.png 2x\"></p>\
+                 <img src=\"data:image/png;base64,AAAA\"><a href=\"https://example.com/\">link</a>"
+            ),
+            format!(
+                "<p>Hi <img src=\"{a}\" srcset=\"{a} 1x, {b} 2x\"></p>\
+                 <img src=\"data:image/png;base64,AAAA\"><a href=\"https://example.com/\">link</a>"
+            )
+        );
+    }
+
+    #[test]
+    fn leaves_media_sources_alone() {
+        let proxy = proxy();
+        let a = proxy
+            .proxied_url("https://images.example.com/a.png")
+            .replace('&', "&amp;");
+
+        assert_eq!(
+            proxy.rewrite_html(
+                "<video><source src=\"https://media.example.com/a.mp4\" type=\"video/mp4\"></video>\
+                 <picture><source srcset=\"https://images.example.com/a.png\"></picture>"
+            ),
+            format!(
+                "<video><source src=\"https://media.example.com/a.mp4\" type=\"video/mp4\"></video>\
+                 <picture><source srcset=\"{a}\"></picture>"
+            )
+        );
+    }
+}
@@ -1,9 +1,14 @@
+use std::time::Duration;
+
 use anyhow::Context;
 use dotenv::dotenv;
 use serde::Deserialize;
 use tracing::warn;
 
-use crate::api::ApiConfig;
+use crate::{
+    api::ApiConfig,
+    feed_loader::{HostLimits, HttpOptions, SyncBounds},
+};
 
 #[derive(Deserialize)]
 pub struct Config {
@@ -11,20 +16,143 @@ pub struct Config {
     pub host: String,
     #[serde(default)]
     pub frontend_dir: Option<String>,
+    /// Signs image proxy URLs. Set it to keep them valid across restarts.
+    #[serde(default)]
+    pub proxy_secret: Option<String>,
+    /// Sent with every request to sites, and matched against robots.txt by
+    /// its first word, e.g. `rss-reader` for `rss-reader/1.0`.
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Where site owners can find out about us, added to the user agent.
+    #[serde(default)]
+    pub user_agent_contact_url: Option<String>,
+    #[serde(default)]
+    pub http_connect_timeout_seconds: Option<u64>,
+    /// Longest wait for the next bit of a response.
+    #[serde(default)]
+    pub http_read_timeout_seconds: Option<u64>,
+    /// HTTP or SOCKS proxy every request to sites goes through, e.g.
+    /// `socks5h://127.0.0.1:9050` for Tor.
+    #[serde(default)]
+    pub http_proxy_url: Option<String>,
+    /// Largest feed or page downloaded, after decompression.
+    #[serde(default)]
+    pub max_response_bytes: Option<usize>,
+    /// Shortest time between syncs of a feed, however often it posts.
+    #[serde(default)]
+    pub sync_min_interval_minutes: Option<u64>,
+    /// Longest time between syncs of a feed, however rarely it posts.
+    #[serde(default)]
+    pub sync_max_interval_minutes: Option<u64>,
+    /// Requests to the same site in flight at once, across all feeds.
+    #[serde(default)]
+    pub host_max_concurrent_requests: Option<usize>,
+    /// Time between the starts of two requests to the same site.
+    #[serde(default)]
+    pub host_min_delay_ms: Option<u64>,
 }
 
 impl Config {
     pub fn new() -> Result<Self, anyhow::Error> {
         let _ = dotenv().map_err(|err| warn!("error loading .env: {:?}", err));
-        envy::from_env::<Config>().context("invalid environment variables")
+        let config = envy::from_env::<Config>().context("invalid environment variables")?;
+        config.sync_bounds()?;
+        config.host_limits()?;
+        config.http_options()?;
+        Ok(config)
+    }
+
+    pub fn http_options(&self) -> Result<HttpOptions, anyhow::Error> {
+        let default = HttpOptions::default();
+        let seconds = |seconds: u64| Duration::from_secs(seconds);
+
+        let user_agent = self.user_agent.clone().unwrap_or(default.user_agent);
+        let user_agent = match &self.user_agent_contact_url {
+            Some(contact_url) => {
+                url::Url::parse(contact_url).context("invalid USER_AGENT_CONTACT_URL")?;
+                format!("{user_agent} (+{contact_url})")
+            }
+            None => user_agent,
+        };
+        let proxy = self
+            .http_proxy_url
+            .as_deref()
+            .map(reqwest::Proxy::all)
+            .transpose()
+            .context("invalid HTTP_PROXY_URL")?;
+
+        let options = HttpOptions {
+            user_agent,
+            connect_timeout: self
+                .http_connect_timeout_seconds
+                .map_or(default.connect_timeout, seconds),
+            read_timeout: self
+                .http_read_timeout_seconds
+                .map_or(default.read_timeout, seconds),
+            proxy,
+            max_response_bytes: self
+                .max_response_bytes
+                .unwrap_or(default.max_response_bytes),
+        };
+
+        if options.connect_timeout.is_zero() || options.read_timeout.is_zero() {
+            anyhow::bail!(
+                "HTTP_CONNECT_TIMEOUT_SECONDS and HTTP_READ_TIMEOUT_SECONDS must be at least 1"
+            );
+        }
+        if options.max_response_bytes == 0 {
+            anyhow::bail!("MAX_RESPONSE_BYTES must be at least 1");
+        }
+        Ok(options)
+    }
+
+    pub fn host_limits(&self) -> Result<HostLimits, anyhow::Error> {
+        let default = HostLimits::default();
+        let limits = HostLimits {
+            max_concurrent: self
+                .host_max_concurrent_requests
+                .unwrap_or(default.max_concurrent),
+            min_delay: self
+                .host_min_delay_ms
+                .map_or(default.min_delay, Duration::from_millis),
+        };
+
+        if limits.max_concurrent == 0 {
+            anyhow::bail!("HOST_MAX_CONCURRENT_REQUESTS must be at least 1");
+        }
+        Ok(limits)
+    }
+
+    pub fn sync_bounds(&self) -> Result<SyncBounds, anyhow::Error> {
+        let default = SyncBounds::default();
+        let minutes = |minutes: u64| Duration::from_secs(minutes.saturating_mul(60));
+        let bounds = SyncBounds {
+            min_interval: self
+                .sync_min_interval_minutes
+                .map_or(default.min_interval, minutes),
+            max_interval: self
+                .sync_max_interval_minutes
+                .map_or(default.max_interval, minutes),
+        };
+
+        if bounds.min_interval.is_zero() {
+            anyhow::bail!("SYNC_MIN_INTERVAL_MINUTES must be at least 1");
+        }
+        if bounds.min_interval > bounds.max_interval {
+            anyhow::bail!("SYNC_MIN_INTERVAL_MINUTES can't be more than SYNC_MAX_INTERVAL_MINUTES");
+        }
+        Ok(bounds)
     }
 }
 
 impl From<Config> for ApiConfig {
     fn from(config: Config) -> Self {
         ApiConfig {
+            // checked by Config::new
+            sync_bounds: config.sync_bounds().unwrap_or_default(),
             host: config.host,
             frontend_dir: config.frontend_dir,
+            proxy_secret: config.proxy_secret,
         }
     }
 }
@@ -1,7 +1,8 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use std::{collections::HashSet, sync::Arc};
+use sqlx::types::Json;
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
 mod id;
 pub use id::*;
@@ -17,7 +18,7 @@ pub trait DataI: Send + Sync {
         &self,
         feed: &NewFeed,
         entries: Vec<NewEntry>,
-        icon: Option<NewIcon>,
+        icon: IconLookup,
     ) -> Result<(), anyhow::Error>;
 
     async fn upsert_entries(
@@ -51,11 +52,23 @@ pub trait DataI: Send + Sync {
         feed_urls: &[String],
     ) -> Result<HashSet<String>, sqlx::Error>;
 
-    async fn get_feeds_to_sync(
+    /// Claims the feeds due at `due_at`. Feeds that were never scheduled are
+    /// due an hour after their last sync, and gone feeds are never due.
+    async fn get_feeds_to_sync(&self, due_at: DateTime<Utc>) -> anyhow::Result<Vec<FeedToSync>>;
+
+    /// When the next feed is due, if any can be synced.
+    async fn get_next_sync_at(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error>;
+
+    /// Schedules the feed's next sync `delay` from now, and remembers its
+    /// usual `interval` for syncs that can't work one out.
+    async fn schedule_feed_sync(
         &self,
-        last_synced_before: DateTime<Utc>,
-    ) -> anyhow::Result<Vec<FeedToSync>>;
+        feed_url: &str,
+        interval: std::time::Duration,
+        delay: std::time::Duration,
+    ) -> Result<(), sqlx::Error>;
 
+    /// Any result but `success` and `not_modified` counts as a failure.
     async fn set_feed_sync_result(&self, feed_url: &str, result: &str) -> Result<(), sqlx::Error>;
 
     async fn update_feed_headers(
@@ -65,6 +78,19 @@ pub trait DataI: Send + Sync {
         last_modified: Option<&str>,
     ) -> Result<(), sqlx::Error>;
 
+    /// Records where the feed permanently redirected to on this sync, or
+    /// `None` if it didn't. Returns how many syncs in a row it has redirected
+    /// to the same place.
+    async fn record_feed_redirect(
+        &self,
+        feed_url: &str,
+        redirected_to: Option<&str>,
+    ) -> Result<i32, sqlx::Error>;
+
+    /// Changes the feed's URL, keeping its entries. Returns false, leaving
+    /// the feed as it is, when another feed already has `new_feed_url`.
+    async fn move_feed(&self, feed_url: &str, new_feed_url: &str) -> Result<bool, sqlx::Error>;
+
     async fn get_one_feed_to_sync(&self, feed_id: &str) -> Result<Option<FeedToSync>, sqlx::Error>;
 
     async fn get_similar_named_feed(
@@ -80,12 +106,38 @@ pub trait DataI: Send + Sync {
         site_url: Option<&str>,
     ) -> Result<(), sqlx::Error>;
 
+    async fn set_feed_fetch_full_content(
+        &self,
+        feed_id: &str,
+        fetch_full_content: bool,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Lets the feed be fetched even where robots.txt disallows it.
+    async fn set_feed_ignore_robots(
+        &self,
+        feed_url: &str,
+        ignore_robots: bool,
+    ) -> Result<(), sqlx::Error>;
+
     async fn delete_feed(&self, feed_id: &str) -> Result<bool, anyhow::Error>;
 
     async fn upsert_icon(&self, icon: NewIcon) -> Result<(), sqlx::Error>;
 
     async fn get_icon_by_feed_id(&self, feed_id: &str) -> Result<Option<Icon>, sqlx::Error>;
 
+    async fn get_proxied_image(&self, url: &str) -> Result<Option<ProxiedImage>, sqlx::Error>;
+
+    async fn upsert_proxied_image(&self, image: &NewProxiedImage) -> Result<(), sqlx::Error>;
+
+    /// Deletes the proxied images fetched before `fetched_before`, then the
+    /// least recently fetched ones until the rest take up at most `max_bytes`.
+    /// Returns how many were deleted.
+    async fn prune_proxied_images(
+        &self,
+        fetched_before: DateTime<Utc>,
+        max_bytes: i64,
+    ) -> Result<u64, sqlx::Error>;
+
     async fn create_opml_import_job(
         &self,
         feed_urls: &[String],
@@ -126,6 +178,33 @@ pub trait DataI: Send + Sync {
 
     async fn update_entry_read_status(&self, entry_id: &str, read: bool)
     -> Result<(), sqlx::Error>;
+
+    async fn get_entry(&self, entry_id: &str) -> Result<Option<Entry>, sqlx::Error>;
+
+    /// Returns false if the entry doesn't exist.
+    async fn update_entry_playback_position(
+        &self,
+        entry_id: &str,
+        position_seconds: Option<i32>,
+    ) -> Result<bool, sqlx::Error>;
+
+    /// Newest entries of the feed published (or, without a date, created)
+    /// after `newer_than` that full content hasn't been extracted for yet,
+    /// successfully or not.
+    async fn get_entries_to_extract(
+        &self,
+        feed_id: &str,
+        newer_than: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<EntryToExtract>, sqlx::Error>;
+
+    /// Records the outcome of extracting an entry's full content, so it isn't
+    /// attempted again.
+    async fn set_entry_extraction(
+        &self,
+        entry_id: &str,
+        result: Result<&str, &str>,
+    ) -> Result<(), sqlx::Error>;
 }
 
 pub type Data = Arc<dyn DataI>;
@@ -134,6 +213,16 @@ pub async fn new_pg_data(database_url: &str) -> Result<Data> {
     pg::new_pg_data(database_url).await
 }
 
+/// What came of looking for a feed's icon while loading the feed.
+#[derive(Debug)]
+pub enum IconLookup {
+    /// The icon was refreshed recently enough.
+    Skipped,
+    /// The existing icon, if any, is kept.
+    NotFound,
+    Found(NewIcon),
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct NewIcon {
     pub hash: String,
@@ -148,6 +237,19 @@ pub struct Icon {
     pub content_type: String,
 }
 
+/// A remote image cached by the image proxy.
+pub struct ProxiedImage {
+    pub content_type: String,
+    pub data: Vec<u8>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+pub struct NewProxiedImage {
+    pub url: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
 #[derive(Debug, Clone)]
 pub struct OpmlImportJobSummary {
     pub job_id: String,
@@ -185,6 +287,10 @@ pub struct QueryFeedsFilters {
     pub limit: Option<u64>,
     pub query: Option<String>,
     pub feed_id: Option<String>,
+    /// Matches entries with this author, case-insensitively.
+    pub author: Option<String>,
+    /// Matches entries with this category, case-insensitively.
+    pub category: Option<String>,
     pub unread: Option<bool>,
     pub starred: Option<bool>,
     pub start: Option<DateTime<Utc>>,
@@ -211,9 +317,23 @@ pub struct Entry {
     pub title: String,
     pub url: String,
     pub comments_url: Option<String>,
+    pub summary: Option<String>,
+    pub summary_type: Option<String>,
+    pub content: Option<String>,
+    pub content_type: Option<String>,
+    pub authors: Vec<String>,
+    pub categories: Vec<String>,
+    pub enclosures: Json<Vec<Enclosure>>,
+    pub playback_position_seconds: Option<i32>,
+    /// Article extracted from the entry's page, for feeds that only ship a
+    /// teaser.
+    pub extracted_content: Option<String>,
+    pub extracted_at: Option<DateTime<Utc>>,
+    pub extraction_error: Option<String>,
     pub read_at: Option<DateTime<Utc>>,
     pub starred_at: Option<DateTime<Utc>>,
     pub published_at: Option<DateTime<Utc>>,
+    pub entry_updated_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -228,6 +348,9 @@ pub struct EntryForList {
     pub starred_at: Option<DateTime<Utc>>,
     pub published_at: Option<DateTime<Utc>>,
     pub entry_updated_at: Option<DateTime<Utc>>,
+    pub authors: Vec<String>,
+    pub categories: Vec<String>,
+    pub enclosures: Json<Vec<Enclosure>>,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -241,6 +364,9 @@ pub struct EntryForQueryList {
     pub starred_at: Option<DateTime<Utc>>,
     pub published_at: Option<DateTime<Utc>>,
     pub entry_updated_at: Option<DateTime<Utc>>,
+    pub authors: Vec<String>,
+    pub categories: Vec<String>,
+    pub enclosures: Json<Vec<Enclosure>>,
     pub has_icon: Option<bool>,
 }
 
@@ -254,13 +380,40 @@ pub struct Feed {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, Default, serde::Serialize)]
 pub struct NewEntry {
     pub title: String,
     pub url: String,
     pub comments_url: Option<String>,
     pub published_at: Option<DateTime<Utc>>,
     pub entry_updated_at: Option<DateTime<Utc>>,
+    pub summary: Option<String>,
+    pub summary_type: Option<String>,
+    pub content: Option<String>,
+    pub content_type: Option<String>,
+    pub authors: Vec<String>,
+    pub categories: Vec<String>,
+    pub enclosures: Vec<Enclosure>,
+    /// RSS `guid`, Atom `id` or JSON Feed `id`.
+    pub guid: Option<String>,
+}
+
+impl NewEntry {
+    /// What identifies the entry within its feed: the guid when there is one,
+    /// the url otherwise.
+    pub fn identity(&self) -> &str {
+        self.guid.as_deref().unwrap_or(&self.url)
+    }
+}
+
+/// Media attached to an entry, e.g. a podcast episode.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Enclosure {
+    pub url: String,
+    pub mime_type: Option<String>,
+    pub length: Option<i64>,
+    pub duration_seconds: Option<i64>,
+    pub image_url: Option<String>,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -270,6 +423,9 @@ pub struct NewFeed {
     pub feed_url: String,
 }
 
+/// How long a feed has to keep failing to sync before it's shown as broken.
+pub const BROKEN_AFTER: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
 #[derive(Debug, serde::Serialize)]
 pub struct FeedWithEntryCounts {
     pub id: String,
@@ -284,6 +440,10 @@ pub struct FeedWithEntryCounts {
     pub has_icon: bool,
     pub last_synced_at: Option<DateTime<Utc>>,
     pub last_sync_result: Option<String>,
+    pub fetch_full_content: bool,
+    pub ignore_robots: bool,
+    /// The feed has failed to sync for [`BROKEN_AFTER`].
+    pub broken: bool,
 }
 
 pub struct FeedToSync {
@@ -292,4 +452,15 @@ pub struct FeedToSync {
     pub site_url: Option<String>,
     pub http_etag: Option<String>,
     pub http_last_modified: Option<String>,
+    pub fetch_full_content: bool,
+    pub ignore_robots: bool,
+    pub icon_refreshed_at: Option<DateTime<Utc>>,
+    pub sync_interval_seconds: Option<i32>,
+    /// Failed syncs since the last successful one.
+    pub consecutive_failures: i32,
+}
+
+pub struct EntryToExtract {
+    pub id: String,
+    pub url: String,
 }
@@ -0,0 +1,4 @@
+alter table entries add column summary text;
+alter table entries add column summary_type text;
+alter table entries add column content text;
+alter table entries add column content_type text;
@@ -0,0 +1,6 @@
+alter table entries add column guid text;
+
+-- entries are identified by their guid when the feed provides one, and by
+-- their url otherwise
+alter table entries drop constraint entries_feed_id_url_key;
+create unique index entries_feed_id_identity_key on entries (feed_id, (coalesce(guid, url)));
@@ -0,0 +1 @@
+alter table entries add column authors text[] not null default '{}';
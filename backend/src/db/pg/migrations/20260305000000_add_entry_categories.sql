@@ -0,0 +1 @@
+alter table entries add column categories text[] not null default '{}';
@@ -0,0 +1,2 @@
+alter table entries add column enclosures jsonb not null default '[]';
+alter table entries add column playback_position_seconds integer;
@@ -0,0 +1,5 @@
+alter table feeds add column fetch_full_content boolean not null default false;
+
+alter table entries add column extracted_content text;
+alter table entries add column extracted_at timestamptz;
+alter table entries add column extraction_error text;
@@ -0,0 +1,8 @@
+create table proxied_images (
+    -- urls can be longer than an index entry allows
+    url_hash text primary key,
+    url text not null,
+    content_type text not null,
+    data bytea not null,
+    fetched_at timestamptz not null default now()
+);
@@ -0,0 +1 @@
+alter table feeds add column icon_refreshed_at timestamptz;
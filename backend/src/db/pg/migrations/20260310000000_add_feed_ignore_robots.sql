@@ -0,0 +1 @@
+alter table feeds add column ignore_robots boolean not null default false;
@@ -0,0 +1,4 @@
+alter table feeds add column next_sync_at timestamptz;
+alter table feeds add column sync_interval_seconds integer;
+
+create index feeds_next_sync_at_idx on feeds(next_sync_at);
@@ -0,0 +1,8 @@
+alter table feeds add column consecutive_failures integer not null default 0;
+alter table feeds add column failing_since timestamptz;
+
+-- feeds retired by a parse error are retried, starting from their last attempt
+update feeds
+set consecutive_failures = 1,
+    failing_since = coalesce(last_synced_at, now())
+where last_sync_result not in ('success', 'not_modified');
@@ -0,0 +1,2 @@
+alter table feeds add column redirected_to text;
+alter table feeds add column redirect_count integer not null default 0;
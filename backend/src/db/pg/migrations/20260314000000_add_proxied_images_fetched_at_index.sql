@@ -0,0 +1,2 @@
+-- pruning goes from the least recently fetched images
+create index proxied_images_fetched_at_idx on proxied_images (fetched_at);
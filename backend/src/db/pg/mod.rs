@@ -1,14 +1,15 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use sqlx::{PgPool, Postgres, QueryBuilder, Row, migrate, query, query_as};
+use sqlx::{PgPool, Postgres, QueryBuilder, Row, migrate, query, query_as, types::Json};
 use std::{collections::HashSet, sync::Arc};
 use tracing::info;
 
 use super::{
-    Cursor, CursorOutput, Data, DataI, EntryForList, EntryForQueryList, FeedToSync,
-    FeedWithEntryCounts, Icon, NewEntry, NewFeed, NewIcon, OpmlImportItem, OpmlImportJob,
-    OpmlImportJobSummary, QueryFeedsFilters, SortOrder, create_id,
+    BROKEN_AFTER, Cursor, CursorOutput, Data, DataI, Enclosure, Entry, EntryForList,
+    EntryForQueryList, EntryToExtract, FeedToSync, FeedWithEntryCounts, Icon, IconLookup, NewEntry,
+    NewFeed, NewIcon, NewProxiedImage, OpmlImportItem, OpmlImportJob, OpmlImportJobSummary,
+    ProxiedImage, QueryFeedsFilters, SortOrder, create_id,
 };
 
 #[cfg(test)]
@@ -52,12 +53,12 @@ impl DataI for PgData {
         &self,
         feed: &NewFeed,
         entries: Vec<NewEntry>,
-        icon: Option<NewIcon>,
+        icon: IconLookup,
     ) -> Result<(), anyhow::Error> {
         let mut seen = HashSet::new();
         let unique_entries: Vec<_> = entries
             .iter()
-            .filter(|entry| seen.insert(entry.url.clone()))
+            .filter(|entry| seen.insert(entry.identity().to_owned()))
             .cloned()
             .collect();
 
@@ -84,7 +85,9 @@ impl DataI for PgData {
                 updated_at = now(),
                 sync_started_at = NULL,
                 last_synced_at = now(),
-                last_sync_result = 'success'
+                last_sync_result = 'success',
+                consecutive_failures = 0,
+                failing_since = NULL
             returning id
             "#,
             create_id(),
@@ -98,8 +101,34 @@ impl DataI for PgData {
         .id;
 
         if !unique_entries.is_empty() {
+            // Entries stored before their feed started sending guids are
+            // adopted by url, so they aren't duplicated once one shows up.
+            let (urls, guids): (Vec<_>, Vec<_>) = unique_entries
+                .iter()
+                .filter_map(|entry| Some((entry.url.clone(), entry.guid.clone()?)))
+                .unzip();
+            query!(
+                r#"
+                update entries e set guid = incoming.guid
+                from unnest($2::text[], $3::text[]) as incoming(url, guid)
+                where e.feed_id = $1
+                    and e.url = incoming.url
+                    and e.guid is null
+                    and not exists (
+                        select 1 from entries other
+                        where other.feed_id = $1 and other.guid = incoming.guid
+                    )
+                "#,
+                feed_id,
+                &urls,
+                &guids
+            )
+            .execute(&mut *tx)
+            .await
+            .context("error adopting entries by guid")?;
+
             let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
-                "insert into entries (id, feed_id, title, url, comments_url, published_at, entry_updated_at)",
+                "insert into entries (id, feed_id, title, url, comments_url, published_at, entry_updated_at, summary, summary_type, content, content_type, authors, categories, enclosures, guid)",
             );
 
             builder.push_values(unique_entries, |mut b, entry| {
@@ -110,16 +139,31 @@ impl DataI for PgData {
                 b.push_bind(entry.comments_url);
                 b.push_bind(entry.published_at);
                 b.push_bind(entry.entry_updated_at);
+                b.push_bind(entry.summary);
+                b.push_bind(entry.summary_type);
+                b.push_bind(entry.content);
+                b.push_bind(entry.content_type);
+                b.push_bind(entry.authors);
+                b.push_bind(entry.categories);
+                b.push_bind(Json(entry.enclosures));
+                b.push_bind(entry.guid);
             });
 
             builder.push(
                 r#"
-                on conflict (feed_id, url) do update set
+                on conflict (feed_id, (coalesce(guid, url))) do update set
                     title = excluded.title,
                     url = excluded.url,
                     comments_url = excluded.comments_url,
                     published_at = excluded.published_at,
-                    entry_updated_at = excluded.entry_updated_at
+                    entry_updated_at = excluded.entry_updated_at,
+                    summary = excluded.summary,
+                    summary_type = excluded.summary_type,
+                    content = excluded.content,
+                    content_type = excluded.content_type,
+                    authors = excluded.authors,
+                    categories = excluded.categories,
+                    enclosures = excluded.enclosures
                 "#,
             );
 
@@ -130,9 +174,11 @@ impl DataI for PgData {
                 .context("error inserting entries")?;
         }
 
-        if let Some(icon) = icon {
+        let icon_looked_up = !matches!(icon, IconLookup::Skipped);
+        if let IconLookup::Found(icon) = icon {
             let icon_id = create_id();
 
+            // a refreshed icon replaces the feed's previous one
             query!(
                 r#"
                 with icon as (
@@ -140,6 +186,9 @@ impl DataI for PgData {
                     on conflict (hash) do update
                         set hash = excluded.hash
                     returning id
+                ), replaced as (
+                    delete from feeds_icons
+                    where feed_id = $5 and icon_id not in (select id from icon)
                 )
                 insert into feeds_icons (feed_id, icon_id)
                 select $5, id from icon
@@ -156,6 +205,16 @@ impl DataI for PgData {
             .context("error upserting icon and feeds_icons")?;
         }
 
+        if icon_looked_up {
+            query!(
+                "update feeds set icon_refreshed_at = now() where id = $1",
+                feed_id
+            )
+            .execute(&mut *tx)
+            .await
+            .context("error updating icon refresh time")?;
+        }
+
         tx.commit().await.context("error committing transaction")?;
 
         Ok(())
@@ -167,7 +226,7 @@ impl DataI for PgData {
         entries: Vec<NewEntry>,
     ) -> Result<(), sqlx::Error> {
         let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
-            "insert into entries (id, feed_id, title, url, comments_url, published_at, entry_updated_at)",
+            "insert into entries (id, feed_id, title, url, comments_url, published_at, entry_updated_at, summary, summary_type, content, content_type, authors, categories, enclosures, guid)",
         );
 
         builder.push_values(entries, |mut b, entry| {
@@ -178,6 +237,14 @@ impl DataI for PgData {
             b.push_bind(entry.comments_url);
             b.push_bind(entry.published_at);
             b.push_bind(entry.entry_updated_at);
+            b.push_bind(entry.summary);
+            b.push_bind(entry.summary_type);
+            b.push_bind(entry.content);
+            b.push_bind(entry.content_type);
+            b.push_bind(entry.authors);
+            b.push_bind(entry.categories);
+            b.push_bind(Json(entry.enclosures));
+            b.push_bind(entry.guid);
         });
 
         builder.build().execute(&self.pg_pool).await?;
@@ -201,6 +268,9 @@ impl DataI for PgData {
                 f.created_at,
                 f.last_synced_at,
                 f.last_sync_result,
+                f.fetch_full_content,
+                f.ignore_robots,
+                coalesce(f.failing_since < now() - make_interval(secs => $2), false) as "broken!",
                 count(e.id) as "entry_count!",
                 count(e.id) filter (where e.read_at is null) as "unread_entry_count!",
                 exists (
@@ -214,7 +284,8 @@ impl DataI for PgData {
             group by f.id
             order by f.created_at desc
             "#,
-            id
+            id,
+            BROKEN_AFTER.as_secs_f64()
         )
         .fetch_optional(&self.pg_pool)
         .await?;
@@ -236,6 +307,9 @@ impl DataI for PgData {
                 f.created_at,
                 f.last_synced_at,
                 f.last_sync_result,
+                f.fetch_full_content,
+                f.ignore_robots,
+                coalesce(f.failing_since < now() - make_interval(secs => $1), false) as "broken!",
                 count(e.id) as "entry_count!",
                 count(e.id) filter (where e.read_at is null) as "unread_entry_count!",
                 exists (
@@ -247,7 +321,8 @@ impl DataI for PgData {
             left join entries e on e.feed_id = f.id
             group by f.id
             order by f.created_at desc
-            "#
+            "#,
+            BROKEN_AFTER.as_secs_f64()
         )
         .fetch_all(&self.pg_pool)
         .await?;
@@ -271,6 +346,9 @@ impl DataI for PgData {
                 e.comments_url,
                 e.published_at,
                 e.entry_updated_at,
+                e.authors,
+                e.categories,
+                e.enclosures,
                 e.read_at,
                 e.starred_at,
                 e.created_at,
@@ -339,6 +417,9 @@ impl DataI for PgData {
                 starred_at: row.get_unchecked("starred_at"),
                 published_at: row.get_unchecked("published_at"),
                 entry_updated_at: row.get_unchecked("entry_updated_at"),
+                authors: row.get_unchecked("authors"),
+                categories: row.get_unchecked("categories"),
+                enclosures: row.get_unchecked("enclosures"),
             })
             .collect();
 
@@ -347,9 +428,8 @@ impl DataI for PgData {
             entries.pop();
         }
 
-        match cursor {
-            Some(Cursor::Left(_)) => entries.reverse(),
-            _ => {}
+        if let Some(Cursor::Left(_)) = cursor {
+            entries.reverse();
         }
 
         let (next_id, prev_id) = if let [first, _second, ..] = &entries[..] {
@@ -390,6 +470,9 @@ impl DataI for PgData {
                 e.comments_url,
                 e.published_at,
                 e.entry_updated_at,
+                e.authors,
+                e.categories,
+                e.enclosures,
                 e.read_at,
                 e.starred_at,
                 e.created_at,
@@ -418,6 +501,22 @@ impl DataI for PgData {
                     .push(")");
             }
 
+            if let Some(ref author) = filters.author {
+                query
+                    .push(" and exists (select 1 from unnest(e.authors) a where lower(a) = lower(")
+                    .push_bind(author)
+                    .push("))");
+            }
+
+            if let Some(ref category) = filters.category {
+                query
+                    .push(
+                        " and exists (select 1 from unnest(e.categories) c where lower(c) = lower(",
+                    )
+                    .push_bind(category)
+                    .push("))");
+            }
+
             if filters.unread == Some(true) {
                 query.push(" and e.read_at is null");
             }
@@ -520,6 +619,9 @@ impl DataI for PgData {
                 starred_at: row.get_unchecked("starred_at"),
                 published_at: row.get_unchecked("published_at"),
                 entry_updated_at: row.get_unchecked("entry_updated_at"),
+                authors: row.get_unchecked("authors"),
+                categories: row.get_unchecked("categories"),
+                enclosures: row.get_unchecked("enclosures"),
                 has_icon: row.get_unchecked("has_icon"),
             })
             .collect();
@@ -529,9 +631,8 @@ impl DataI for PgData {
             entries.pop();
         }
 
-        match cursor {
-            Some(Cursor::Left(_)) => entries.reverse(),
-            _ => {}
+        if let Some(Cursor::Left(_)) = cursor {
+            entries.reverse();
         }
 
         let (next_id, prev_id) = if let [first, _second, ..] = &entries[..] {
@@ -579,10 +680,7 @@ impl DataI for PgData {
         Ok(rows.into_iter().map(|row| row.feed_url).collect())
     }
 
-    async fn get_feeds_to_sync(
-        &self,
-        last_synced_before: DateTime<Utc>,
-    ) -> anyhow::Result<Vec<FeedToSync>> {
+    async fn get_feeds_to_sync(&self, due_at: DateTime<Utc>) -> anyhow::Result<Vec<FeedToSync>> {
         let feeds = sqlx::query_as!(
             FeedToSync,
             r#"
@@ -591,17 +689,24 @@ impl DataI for PgData {
             where id in (
                 select id
                 from feeds f
-                where f.last_sync_result is distinct from 'parse_error'
+                where f.last_sync_result is distinct from 'gone'
                 and (
-                    (f.sync_started_at is null and (f.last_synced_at < $1 or f.last_synced_at is null))
+                    (
+                        f.sync_started_at is null
+                        and coalesce(
+                            f.next_sync_at,
+                            f.last_synced_at + interval '1 hour',
+                            '-infinity'
+                        ) <= $1
+                    )
                     or f.sync_started_at < now() - interval '5 minutes'
                 )
-                order by f.last_synced_at desc nulls first
+                order by f.next_sync_at nulls first
                 for update skip locked
             )
-            returning f.id, f.feed_url, f.site_url, f.http_etag, f.http_last_modified
+            returning f.id, f.feed_url, f.site_url, f.http_etag, f.http_last_modified, f.fetch_full_content, f.ignore_robots, f.icon_refreshed_at, f.sync_interval_seconds, f.consecutive_failures
             "#,
-            last_synced_before
+            due_at
         )
         .fetch_all(&self.pg_pool)
         .await?;
@@ -609,13 +714,70 @@ impl DataI for PgData {
         Ok(feeds)
     }
 
+    async fn get_next_sync_at(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let row = query!(
+            r#"
+            select min(
+                case
+                    when f.sync_started_at is not null
+                        then f.sync_started_at + interval '5 minutes'
+                    else coalesce(
+                        f.next_sync_at,
+                        f.last_synced_at + interval '1 hour',
+                        now()
+                    )
+                end
+            ) as next_sync_at
+            from feeds f
+            where f.last_sync_result is distinct from 'gone'
+            "#
+        )
+        .fetch_one(&self.pg_pool)
+        .await?;
+
+        Ok(row.next_sync_at)
+    }
+
+    async fn schedule_feed_sync(
+        &self,
+        feed_url: &str,
+        interval: std::time::Duration,
+        delay: std::time::Duration,
+    ) -> Result<(), sqlx::Error> {
+        let seconds =
+            |duration: std::time::Duration| i32::try_from(duration.as_secs()).unwrap_or(i32::MAX);
+        query!(
+            r#"
+            update feeds
+            set sync_interval_seconds = $2,
+                next_sync_at = now() + make_interval(secs => $3::integer)
+            where feed_url = $1
+            "#,
+            feed_url,
+            seconds(interval),
+            seconds(delay)
+        )
+        .execute(&self.pg_pool)
+        .await?;
+
+        Ok(())
+    }
+
     async fn set_feed_sync_result(&self, feed_url: &str, result: &str) -> Result<(), sqlx::Error> {
         query!(
             r#"
             update feeds
             set last_sync_result = $2,
                 sync_started_at = null,
-                updated_at = now()
+                updated_at = now(),
+                consecutive_failures = case
+                    when $2 in ('success', 'not_modified') then 0
+                    else consecutive_failures + 1
+                end,
+                failing_since = case
+                    when $2 in ('success', 'not_modified') then null
+                    else coalesce(failing_since, now())
+                end
             where feed_url = $1
             "#,
             feed_url,
@@ -663,7 +825,7 @@ impl DataI for PgData {
                 where id = $1
                 for update skip locked
             )
-            returning f.id, f.feed_url, f.site_url, f.http_etag, f.http_last_modified
+            returning f.id, f.feed_url, f.site_url, f.http_etag, f.http_last_modified, f.fetch_full_content, f.ignore_robots, f.icon_refreshed_at, f.sync_interval_seconds, f.consecutive_failures
             "#,
             feed_id
         )
@@ -682,7 +844,7 @@ impl DataI for PgData {
         let feed = sqlx::query_as!(
             FeedToSync,
             r#"
-            select f.id, f.feed_url, f.site_url, f.http_etag, f.http_last_modified
+            select f.id, f.feed_url, f.site_url, f.http_etag, f.http_last_modified, f.fetch_full_content, f.ignore_robots, f.icon_refreshed_at, f.sync_interval_seconds, f.consecutive_failures
             from feeds f
             where f.feed_url like $1
             limit 1
@@ -695,6 +857,53 @@ impl DataI for PgData {
         Ok(feed)
     }
 
+    async fn record_feed_redirect(
+        &self,
+        feed_url: &str,
+        redirected_to: Option<&str>,
+    ) -> Result<i32, sqlx::Error> {
+        let row = query!(
+            r#"
+            update feeds
+            set redirect_count = case
+                    when $2::text is null then 0
+                    when redirected_to = $2 then redirect_count + 1
+                    else 1
+                end,
+                redirected_to = $2
+            where feed_url = $1
+            returning redirect_count
+            "#,
+            feed_url,
+            redirected_to
+        )
+        .fetch_optional(&self.pg_pool)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+        Ok(row.redirect_count)
+    }
+
+    async fn move_feed(&self, feed_url: &str, new_feed_url: &str) -> Result<bool, sqlx::Error> {
+        let moved = query!(
+            r#"
+            update feeds
+            set feed_url = $2,
+                redirected_to = null,
+                redirect_count = 0,
+                updated_at = now()
+            where feed_url = $1
+            and not exists (select 1 from feeds where feed_url = $2)
+            "#,
+            feed_url,
+            new_feed_url
+        )
+        .execute(&self.pg_pool)
+        .await?;
+
+        Ok(moved.rows_affected() > 0)
+    }
+
     async fn update_feed(
         &self,
         feed_id: &str,
@@ -727,6 +936,58 @@ impl DataI for PgData {
         Ok(())
     }
 
+    async fn set_feed_fetch_full_content(
+        &self,
+        feed_id: &str,
+        fetch_full_content: bool,
+    ) -> Result<(), sqlx::Error> {
+        let updated = query!(
+            r#"
+            update feeds
+            set fetch_full_content = $2,
+                updated_at = now()
+            where id = $1
+            returning id
+            "#,
+            feed_id,
+            fetch_full_content
+        )
+        .fetch_optional(&self.pg_pool)
+        .await?;
+
+        if updated.is_none() {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn set_feed_ignore_robots(
+        &self,
+        feed_url: &str,
+        ignore_robots: bool,
+    ) -> Result<(), sqlx::Error> {
+        let updated = query!(
+            r#"
+            update feeds
+            set ignore_robots = $2,
+                updated_at = now()
+            where feed_url = $1
+            returning id
+            "#,
+            feed_url,
+            ignore_robots
+        )
+        .fetch_optional(&self.pg_pool)
+        .await?;
+
+        if updated.is_none() {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
     async fn delete_feed(&self, feed_id: &str) -> Result<bool, anyhow::Error> {
         let mut tx = self
             .pg_pool
@@ -800,6 +1061,8 @@ impl DataI for PgData {
             inner join feeds_icons as fi
                 on i.id = fi.icon_id
             where fi.feed_id = $1
+            order by fi.created_at desc
+            limit 1
             "#,
             feed_id
         )
@@ -809,6 +1072,71 @@ impl DataI for PgData {
         Ok(icon)
     }
 
+    async fn get_proxied_image(&self, url: &str) -> Result<Option<ProxiedImage>, sqlx::Error> {
+        query_as!(
+            ProxiedImage,
+            r#"
+            select content_type, data, fetched_at
+            from proxied_images
+            where url_hash = encode(sha256(convert_to($1, 'UTF8')), 'hex')
+            "#,
+            url
+        )
+        .fetch_optional(&self.pg_pool)
+        .await
+    }
+
+    async fn upsert_proxied_image(&self, image: &NewProxiedImage) -> Result<(), sqlx::Error> {
+        query!(
+            r#"
+            insert into proxied_images (url_hash, url, content_type, data)
+            values (encode(sha256(convert_to($1, 'UTF8')), 'hex'), $1, $2, $3)
+            on conflict (url_hash) do update set
+                content_type = $2,
+                data = $3,
+                fetched_at = now()
+            "#,
+            image.url,
+            image.content_type,
+            image.data
+        )
+        .execute(&self.pg_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn prune_proxied_images(
+        &self,
+        fetched_before: DateTime<Utc>,
+        max_bytes: i64,
+    ) -> Result<u64, sqlx::Error> {
+        let result = query!(
+            r#"
+            delete from proxied_images
+            where fetched_at < $1
+                or url_hash in (
+                    select url_hash
+                    from (
+                        select
+                            url_hash,
+                            sum(octet_length(data)) over (
+                                order by fetched_at desc, url_hash
+                            ) as total_bytes
+                        from proxied_images
+                    ) sized
+                    where total_bytes > $2
+                )
+            "#,
+            fetched_before,
+            max_bytes
+        )
+        .execute(&self.pg_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     async fn create_opml_import_job(
         &self,
         feed_urls: &[String],
@@ -1025,4 +1353,116 @@ impl DataI for PgData {
 
         Ok(())
     }
+
+    async fn get_entry(&self, entry_id: &str) -> Result<Option<Entry>, sqlx::Error> {
+        let entry = query_as!(
+            Entry,
+            r#"
+            select
+                id,
+                feed_id,
+                title,
+                url,
+                comments_url,
+                summary,
+                summary_type,
+                content,
+                content_type,
+                authors,
+                categories,
+                enclosures as "enclosures: Json<Vec<Enclosure>>",
+                playback_position_seconds,
+                extracted_content,
+                extracted_at,
+                extraction_error,
+                read_at,
+                starred_at,
+                published_at,
+                entry_updated_at,
+                created_at,
+                updated_at
+            from entries
+            where id = $1
+            "#,
+            entry_id
+        )
+        .fetch_optional(&self.pg_pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    async fn update_entry_playback_position(
+        &self,
+        entry_id: &str,
+        position_seconds: Option<i32>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = query!(
+            r#"
+            update entries
+            set playback_position_seconds = $2,
+                updated_at = now()
+            where id = $1
+            "#,
+            entry_id,
+            position_seconds
+        )
+        .execute(&self.pg_pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_entries_to_extract(
+        &self,
+        feed_id: &str,
+        newer_than: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<EntryToExtract>, sqlx::Error> {
+        query_as!(
+            EntryToExtract,
+            r#"
+            select id, url
+            from entries
+            where feed_id = $1
+                and extracted_at is null
+                and coalesce(published_at, created_at) > $2
+            order by coalesce(published_at, created_at) desc
+            limit $3
+            "#,
+            feed_id,
+            newer_than,
+            limit
+        )
+        .fetch_all(&self.pg_pool)
+        .await
+    }
+
+    async fn set_entry_extraction(
+        &self,
+        entry_id: &str,
+        result: Result<&str, &str>,
+    ) -> Result<(), sqlx::Error> {
+        let (content, error) = match result {
+            Ok(content) => (Some(content), None),
+            Err(error) => (None, Some(error)),
+        };
+
+        query!(
+            r#"
+            update entries
+            set extracted_content = $2,
+                extraction_error = $3,
+                extracted_at = now()
+            where id = $1
+            "#,
+            entry_id,
+            content,
+            error
+        )
+        .execute(&self.pg_pool)
+        .await?;
+
+        Ok(())
+    }
 }
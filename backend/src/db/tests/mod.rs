@@ -5,7 +5,10 @@
 
 mod pg;
 
-use crate::db::{Cursor, DataI, NewEntry, NewFeed, NewIcon, QueryFeedsFilters, SortOrder};
+use crate::db::{
+    Cursor, DataI, Enclosure, IconLookup, NewEntry, NewFeed, NewIcon, NewProxiedImage,
+    QueryFeedsFilters, SortOrder,
+};
 use chrono::{Duration, Utc};
 use std::collections::HashSet;
 
@@ -28,6 +31,7 @@ fn new_test_entry(title: &str, url: &str) -> NewEntry {
         comments_url: None,
         published_at: None,
         entry_updated_at: None,
+        ..Default::default()
     }
 }
 
@@ -53,7 +57,7 @@ pub(super) async fn test_create_feed(db: &dyn DataI) {
         new_test_entry("Entry 2", "https://example.com/entry2"),
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -76,7 +80,7 @@ pub(super) async fn test_create_feed(db: &dyn DataI) {
 pub(super) async fn test_create_feed_without_entries(db: &dyn DataI) {
     let feed = new_test_feed("Empty Feed", "https://empty.example.com/feed.xml");
 
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -100,7 +104,7 @@ pub(super) async fn test_create_feed_with_icon(db: &dyn DataI) {
         content_type: "image/png".to_string(),
     };
 
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], Some(icon))
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Found(icon))
         .await
         .unwrap();
 
@@ -118,6 +122,50 @@ pub(super) async fn test_create_feed_with_icon(db: &dyn DataI) {
     assert_eq!(icon.content_type, "image/png");
 }
 
+/// Test that the time an icon was looked for is recorded, whether or not one
+/// was found, and left alone when the lookup was skipped.
+pub(super) async fn test_icon_refreshed_at(db: &dyn DataI) {
+    let feed = new_test_feed("Icon Feed", "https://icon.example.com/feed.xml");
+
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
+        .await
+        .unwrap();
+    let feed_id = db.get_feeds_with_entry_counts().await.unwrap()[0]
+        .id
+        .clone();
+    let refreshed_at = async || {
+        db.get_one_feed_to_sync(&feed_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .icon_refreshed_at
+    };
+    assert!(refreshed_at().await.is_none());
+
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::NotFound)
+        .await
+        .unwrap();
+    let not_found_at = refreshed_at().await;
+    assert!(not_found_at.is_some());
+
+    let icon = NewIcon {
+        hash: "refreshed_icon_hash".to_string(),
+        data: vec![0x89, 0x50, 0x4E, 0x47],
+        content_type: "image/png".to_string(),
+    };
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Found(icon))
+        .await
+        .unwrap();
+    let found_at = refreshed_at().await;
+    assert!(found_at >= not_found_at);
+
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
+        .await
+        .unwrap();
+    assert_eq!(refreshed_at().await, found_at);
+    assert!(db.get_icon_by_feed_id(&feed_id).await.unwrap().is_some());
+}
+
 // ----------------------------------------------------------------------------
 // Upsert behavior tests
 // ----------------------------------------------------------------------------
@@ -130,7 +178,7 @@ pub(super) async fn test_upsert_feed_updates_existing(db: &dyn DataI) {
         feed_url: "https://upsert-update.example.com/feed.xml".to_string(),
         site_url: Some("https://original-site.example.com".to_string()),
     };
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -145,7 +193,7 @@ pub(super) async fn test_upsert_feed_updates_existing(db: &dyn DataI) {
         feed_url: "https://upsert-update.example.com/feed.xml".to_string(),
         site_url: Some("https://updated-site.example.com".to_string()),
     };
-    db.upsert_feed_and_entries_and_icon(&updated_feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&updated_feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -174,8 +222,9 @@ pub(super) async fn test_upsert_entries_updates_existing(db: &dyn DataI) {
         comments_url: None,
         published_at: Some(Utc::now() - Duration::days(1)),
         entry_updated_at: None,
+        ..Default::default()
     };
-    db.upsert_feed_and_entries_and_icon(&feed, vec![initial_entry], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![initial_entry], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -193,8 +242,9 @@ pub(super) async fn test_upsert_entries_updates_existing(db: &dyn DataI) {
         comments_url: Some("https://entry-update.example.com/comments".to_string()),
         published_at: Some(Utc::now()),
         entry_updated_at: None,
+        ..Default::default()
     };
-    db.upsert_feed_and_entries_and_icon(&feed, vec![updated_entry], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![updated_entry], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -220,6 +270,7 @@ pub(super) async fn test_upsert_feed_deduplicates_entries(db: &dyn DataI) {
             comments_url: None,
             published_at: None,
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Second Version".to_string(),
@@ -227,6 +278,7 @@ pub(super) async fn test_upsert_feed_deduplicates_entries(db: &dyn DataI) {
             comments_url: None,
             published_at: None,
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Unique Entry".to_string(),
@@ -234,10 +286,11 @@ pub(super) async fn test_upsert_feed_deduplicates_entries(db: &dyn DataI) {
             comments_url: None,
             published_at: None,
             entry_updated_at: None,
+            ..Default::default()
         },
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -259,6 +312,91 @@ pub(super) async fn test_upsert_feed_deduplicates_entries(db: &dyn DataI) {
     assert_eq!(entry1.title, "First Version");
 }
 
+/// Test that entries with a guid keep their identity when their URL changes.
+pub(super) async fn test_upsert_entries_keyed_by_guid(db: &dyn DataI) {
+    let feed = new_test_feed("Guid Feed", "https://guid.example.com/feed.xml");
+
+    let entry = NewEntry {
+        guid: Some("tag:guid.example.com,2026:1".to_string()),
+        ..new_test_entry("Entry", "http://guid.example.com/entry?utm_source=rss")
+    };
+    db.upsert_feed_and_entries_and_icon(&feed, vec![entry], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let moved = NewEntry {
+        guid: Some("tag:guid.example.com,2026:1".to_string()),
+        ..new_test_entry("Entry", "https://guid.example.com/entry")
+    };
+    db.upsert_feed_and_entries_and_icon(&feed, vec![moved], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let feeds = db.get_feeds_with_entry_counts().await.unwrap();
+    let entries = db.get_feed_entries(&feeds[0].id, None, None).await.unwrap();
+    assert_eq!(entries.entries.len(), 1);
+    assert_eq!(entries.entries[0].url, "https://guid.example.com/entry");
+}
+
+/// Test that entries with different guids sharing a URL are all kept.
+pub(super) async fn test_upsert_entries_sharing_url(db: &dyn DataI) {
+    let feed = new_test_feed("Link Blog", "https://links.example.com/feed.xml");
+
+    let entries = vec![
+        NewEntry {
+            guid: Some("links-1".to_string()),
+            ..new_test_entry("First take", "https://elsewhere.example.com/article")
+        },
+        NewEntry {
+            guid: Some("links-2".to_string()),
+            ..new_test_entry("Second take", "https://elsewhere.example.com/article")
+        },
+    ];
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let feeds = db.get_feeds_with_entry_counts().await.unwrap();
+    assert_eq!(feeds[0].entry_count, 2);
+}
+
+/// Test that entries stored without a guid are matched by URL once the feed
+/// starts providing guids, rather than duplicated.
+pub(super) async fn test_upsert_entries_adopts_guid(db: &dyn DataI) {
+    let feed = new_test_feed("Late Guid Feed", "https://late-guid.example.com/feed.xml");
+
+    let entry = new_test_entry("Entry", "https://late-guid.example.com/entry");
+    db.upsert_feed_and_entries_and_icon(&feed, vec![entry], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let with_guid = NewEntry {
+        guid: Some("late-guid-1".to_string()),
+        ..new_test_entry("Entry, retitled", "https://late-guid.example.com/entry")
+    };
+    db.upsert_feed_and_entries_and_icon(&feed, vec![with_guid.clone()], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    // the url can now change without creating a new entry
+    let moved = NewEntry {
+        url: "https://late-guid.example.com/entry-moved".to_string(),
+        ..with_guid
+    };
+    db.upsert_feed_and_entries_and_icon(&feed, vec![moved], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let feeds = db.get_feeds_with_entry_counts().await.unwrap();
+    let entries = db.get_feed_entries(&feeds[0].id, None, None).await.unwrap();
+    assert_eq!(entries.entries.len(), 1);
+    assert_eq!(entries.entries[0].title, "Entry, retitled");
+    assert_eq!(
+        entries.entries[0].url,
+        "https://late-guid.example.com/entry-moved"
+    );
+}
+
 // ----------------------------------------------------------------------------
 // Read feed tests
 // ----------------------------------------------------------------------------
@@ -267,7 +405,7 @@ pub(super) async fn test_upsert_feed_deduplicates_entries(db: &dyn DataI) {
 pub(super) async fn test_get_feed_by_id(db: &dyn DataI) {
     // Create a feed first
     let feed = new_test_feed("Read Test Feed", "https://read.example.com/feed.xml");
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -300,10 +438,10 @@ pub(super) async fn test_get_existing_feed_urls(db: &dyn DataI) {
     let feed1 = new_test_feed("Feed 1", "https://feed1.example.com/feed.xml");
     let feed2 = new_test_feed("Feed 2", "https://feed2.example.com/feed.xml");
 
-    db.upsert_feed_and_entries_and_icon(&feed1, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed1, vec![], IconLookup::Skipped)
         .await
         .unwrap();
-    db.upsert_feed_and_entries_and_icon(&feed2, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed2, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -335,7 +473,7 @@ pub(super) async fn test_get_existing_feed_urls_empty(db: &dyn DataI) {
 /// Test inserting entries for an existing feed.
 pub(super) async fn test_upsert_entries(db: &dyn DataI) {
     let feed = new_test_feed("Upsert Entries Feed", "https://upsert.example.com/feed.xml");
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -359,7 +497,7 @@ pub(super) async fn test_upsert_entries(db: &dyn DataI) {
 /// Test listing entries for a feed with no entries.
 pub(super) async fn test_get_feed_entries_empty(db: &dyn DataI) {
     let feed = new_test_feed("Empty Entries Feed", "https://entries.example.com/feed.xml");
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -381,7 +519,7 @@ pub(super) async fn test_get_feed_entries_limit(db: &dyn DataI) {
         new_test_entry("Limit Entry 3", "https://limit.example.com/entry3"),
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -402,7 +540,7 @@ pub(super) async fn test_get_feed_entries_cursor(db: &dyn DataI) {
         new_test_entry("Cursor Entry 3", "https://cursor.example.com/entry3"),
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -445,6 +583,7 @@ pub(super) async fn test_get_feed_entries_cursor_left(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(now - Duration::hours(4)),
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Entry 2".to_string(),
@@ -452,6 +591,7 @@ pub(super) async fn test_get_feed_entries_cursor_left(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(now - Duration::hours(3)),
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Entry 3".to_string(),
@@ -459,6 +599,7 @@ pub(super) async fn test_get_feed_entries_cursor_left(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(now - Duration::hours(2)),
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Entry 4".to_string(),
@@ -466,10 +607,11 @@ pub(super) async fn test_get_feed_entries_cursor_left(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(now - Duration::hours(1)),
             entry_updated_at: None,
+            ..Default::default()
         },
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -522,6 +664,7 @@ pub(super) async fn test_query_entries_cursor_pagination(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(now - Duration::hours(4)),
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Query Entry 2".to_string(),
@@ -529,6 +672,7 @@ pub(super) async fn test_query_entries_cursor_pagination(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(now - Duration::hours(3)),
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Query Entry 3".to_string(),
@@ -536,6 +680,7 @@ pub(super) async fn test_query_entries_cursor_pagination(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(now - Duration::hours(2)),
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Query Entry 4".to_string(),
@@ -543,10 +688,11 @@ pub(super) async fn test_query_entries_cursor_pagination(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(now - Duration::hours(1)),
             entry_updated_at: None,
+            ..Default::default()
         },
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -555,6 +701,8 @@ pub(super) async fn test_query_entries_cursor_pagination(db: &dyn DataI) {
         limit: Some(2),
         query: None,
         feed_id: None,
+        author: None,
+        category: None,
         unread: None,
         starred: None,
         start: None,
@@ -574,6 +722,8 @@ pub(super) async fn test_query_entries_cursor_pagination(db: &dyn DataI) {
         limit: Some(2),
         query: None,
         feed_id: None,
+        author: None,
+        category: None,
         unread: None,
         starred: None,
         start: None,
@@ -596,6 +746,8 @@ pub(super) async fn test_query_entries_cursor_pagination(db: &dyn DataI) {
         limit: Some(2),
         query: None,
         feed_id: None,
+        author: None,
+        category: None,
         unread: None,
         starred: None,
         start: None,
@@ -622,7 +774,7 @@ pub(super) async fn test_query_entries_no_filters(db: &dyn DataI) {
         new_test_entry("Query Entry 2", "https://query.example.com/entry2"),
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -641,7 +793,7 @@ pub(super) async fn test_query_entries_filter_feed_id(db: &dyn DataI) {
             "Feed A Entry",
             "https://query-a.example.com/entry1",
         )],
-        None,
+        IconLookup::Skipped,
     )
     .await
     .unwrap();
@@ -652,7 +804,7 @@ pub(super) async fn test_query_entries_filter_feed_id(db: &dyn DataI) {
             "Feed B Entry",
             "https://query-b.example.com/entry1",
         )],
-        None,
+        IconLookup::Skipped,
     )
     .await
     .unwrap();
@@ -669,6 +821,8 @@ pub(super) async fn test_query_entries_filter_feed_id(db: &dyn DataI) {
         limit: None,
         query: None,
         feed_id: Some(feed_id.clone()),
+        author: None,
+        category: None,
         unread: None,
         starred: None,
         start: None,
@@ -691,6 +845,7 @@ pub(super) async fn test_query_entries_filter_sort_and_limit(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(Utc::now() - Duration::days(2)),
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Newer Entry".to_string(),
@@ -698,6 +853,7 @@ pub(super) async fn test_query_entries_filter_sort_and_limit(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(Utc::now() - Duration::days(1)),
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Newest Entry".to_string(),
@@ -705,10 +861,11 @@ pub(super) async fn test_query_entries_filter_sort_and_limit(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(Utc::now()),
             entry_updated_at: None,
+            ..Default::default()
         },
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -716,6 +873,8 @@ pub(super) async fn test_query_entries_filter_sort_and_limit(db: &dyn DataI) {
         limit: Some(2),
         query: None,
         feed_id: None,
+        author: None,
+        category: None,
         unread: None,
         starred: None,
         start: None,
@@ -737,7 +896,7 @@ pub(super) async fn test_query_entries_filter_unread(db: &dyn DataI) {
         new_test_entry("Unread Entry 2", "https://unread.example.com/entry2"),
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -745,6 +904,8 @@ pub(super) async fn test_query_entries_filter_unread(db: &dyn DataI) {
         limit: None,
         query: None,
         feed_id: None,
+        author: None,
+        category: None,
         unread: Some(true),
         starred: None,
         start: None,
@@ -764,6 +925,91 @@ pub(super) async fn test_query_entries_empty(db: &dyn DataI) {
     assert!(result.prev_id.is_none());
 }
 
+/// Test querying entries by author and by category.
+pub(super) async fn test_query_entries_filter_author_and_category(db: &dyn DataI) {
+    let feed = new_test_feed("Magazine", "https://magazine.example.com/feed.xml");
+    let entries = vec![
+        NewEntry {
+            authors: vec!["Jane Doe".to_string()],
+            categories: vec!["Science".to_string(), "Space".to_string()],
+            ..new_test_entry("Jane on space", "https://magazine.example.com/1")
+        },
+        NewEntry {
+            authors: vec!["John Roe".to_string(), "Jane Doe".to_string()],
+            categories: vec!["Politics".to_string()],
+            ..new_test_entry(
+                "Jane and John on politics",
+                "https://magazine.example.com/2",
+            )
+        },
+        NewEntry {
+            authors: vec!["John Roe".to_string()],
+            categories: vec!["Science".to_string()],
+            ..new_test_entry("John on science", "https://magazine.example.com/3")
+        },
+    ];
+
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let filters = QueryFeedsFilters {
+        limit: None,
+        query: None,
+        feed_id: None,
+        author: Some("jane doe".to_string()),
+        category: None,
+        unread: None,
+        starred: None,
+        start: None,
+        end: None,
+        sort: None,
+    };
+    let result = db.query_entries(None, Some(filters)).await.unwrap();
+    let titles: HashSet<_> = result.entries.iter().map(|e| e.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        HashSet::from(["Jane on space", "Jane and John on politics"])
+    );
+
+    let filters = QueryFeedsFilters {
+        limit: None,
+        query: None,
+        feed_id: None,
+        author: Some("John Roe".to_string()),
+        category: Some("science".to_string()),
+        unread: None,
+        starred: None,
+        start: None,
+        end: None,
+        sort: None,
+    };
+    let result = db.query_entries(None, Some(filters)).await.unwrap();
+    assert_eq!(result.entries.len(), 1);
+    assert_eq!(result.entries[0].title, "John on science");
+    assert_eq!(result.entries[0].authors, vec!["John Roe"]);
+    assert_eq!(result.entries[0].categories, vec!["Science"]);
+
+    let feed_entries = db
+        .get_feed_entries(&result.entries[0].feed_id, None, None)
+        .await
+        .unwrap();
+    let feed_entry = feed_entries
+        .entries
+        .iter()
+        .find(|entry| entry.title == "Jane and John on politics")
+        .expect("entry");
+    assert_eq!(feed_entry.authors, vec!["John Roe", "Jane Doe"]);
+    assert_eq!(feed_entry.categories, vec!["Politics"]);
+
+    let entry = db
+        .get_entry(&result.entries[0].id)
+        .await
+        .unwrap()
+        .expect("entry");
+    assert_eq!(entry.categories, vec!["Science"]);
+}
+
 /// Test querying entries with starred filter.
 pub(super) async fn test_query_entries_filter_starred(db: &dyn DataI) {
     // Note: We can't directly set starred_at through the DataI trait currently,
@@ -778,7 +1024,7 @@ pub(super) async fn test_query_entries_filter_starred(db: &dyn DataI) {
         new_test_entry("Entry 2", "https://starred.example.com/entry2"),
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -787,6 +1033,8 @@ pub(super) async fn test_query_entries_filter_starred(db: &dyn DataI) {
         limit: None,
         query: None,
         feed_id: None,
+        author: None,
+        category: None,
         unread: None,
         starred: Some(true),
         start: None,
@@ -812,7 +1060,7 @@ pub(super) async fn test_query_entries_filter_query_search(db: &dyn DataI) {
         new_test_entry("JavaScript Basics", "https://search.example.com/javascript"),
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -821,6 +1069,8 @@ pub(super) async fn test_query_entries_filter_query_search(db: &dyn DataI) {
         limit: None,
         query: Some("Rust".to_string()),
         feed_id: None,
+        author: None,
+        category: None,
         unread: None,
         starred: None,
         start: None,
@@ -837,6 +1087,8 @@ pub(super) async fn test_query_entries_filter_query_search(db: &dyn DataI) {
         limit: None,
         query: Some("python".to_string()),
         feed_id: None,
+        author: None,
+        category: None,
         unread: None,
         starred: None,
         start: None,
@@ -853,6 +1105,8 @@ pub(super) async fn test_query_entries_filter_query_search(db: &dyn DataI) {
         limit: None,
         query: Some("golang".to_string()),
         feed_id: None,
+        author: None,
+        category: None,
         unread: None,
         starred: None,
         start: None,
@@ -875,6 +1129,7 @@ pub(super) async fn test_query_entries_filter_date_range(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(now - Duration::days(10)),
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Recent Entry".to_string(),
@@ -882,6 +1137,7 @@ pub(super) async fn test_query_entries_filter_date_range(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(now - Duration::days(3)),
             entry_updated_at: None,
+            ..Default::default()
         },
         NewEntry {
             title: "Today Entry".to_string(),
@@ -889,10 +1145,11 @@ pub(super) async fn test_query_entries_filter_date_range(db: &dyn DataI) {
             comments_url: None,
             published_at: Some(now),
             entry_updated_at: None,
+            ..Default::default()
         },
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -901,6 +1158,8 @@ pub(super) async fn test_query_entries_filter_date_range(db: &dyn DataI) {
         limit: None,
         query: None,
         feed_id: None,
+        author: None,
+        category: None,
         unread: None,
         starred: None,
         start: Some(now - Duration::days(5)),
@@ -918,6 +1177,8 @@ pub(super) async fn test_query_entries_filter_date_range(db: &dyn DataI) {
         limit: None,
         query: None,
         feed_id: None,
+        author: None,
+        category: None,
         unread: None,
         starred: None,
         start: None,
@@ -934,6 +1195,8 @@ pub(super) async fn test_query_entries_filter_date_range(db: &dyn DataI) {
         limit: None,
         query: None,
         feed_id: None,
+        author: None,
+        category: None,
         unread: None,
         starred: None,
         start: Some(now - Duration::days(5)),
@@ -946,6 +1209,237 @@ pub(super) async fn test_query_entries_filter_date_range(db: &dyn DataI) {
     assert_eq!(result.entries[0].title, "Recent Entry");
 }
 
+/// Test that entry summary and content are stored and returned by get_entry.
+pub(super) async fn test_get_entry_with_content(db: &dyn DataI) {
+    let feed = new_test_feed("Content Feed", "https://content.example.com/feed.xml");
+    let entry = NewEntry {
+        title: "Content Entry".to_string(),
+        url: "https://content.example.com/entry1".to_string(),
+        summary: Some("A short summary".to_string()),
+        summary_type: Some("text".to_string()),
+        content: Some("<p>The full article</p>".to_string()),
+        content_type: Some("html".to_string()),
+        authors: vec!["Jane Doe".to_string(), "John Doe".to_string()],
+        ..Default::default()
+    };
+
+    db.upsert_feed_and_entries_and_icon(&feed, vec![entry], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let feeds = db.get_feeds_with_entry_counts().await.unwrap();
+    let feed_id = feeds[0].id.clone();
+    let entries = db.get_feed_entries(&feed_id, None, None).await.unwrap();
+    let entry_id = entries.entries[0].id.clone();
+
+    let entry = db.get_entry(&entry_id).await.unwrap().expect("entry");
+    assert_eq!(entry.feed_id, feed_id);
+    assert_eq!(entry.title, "Content Entry");
+    assert_eq!(entry.summary, Some("A short summary".to_string()));
+    assert_eq!(entry.summary_type, Some("text".to_string()));
+    assert_eq!(entry.content, Some("<p>The full article</p>".to_string()));
+    assert_eq!(entry.content_type, Some("html".to_string()));
+    assert_eq!(entry.authors, vec!["Jane Doe", "John Doe"]);
+}
+
+/// Test that getting a non-existent entry returns None.
+pub(super) async fn test_get_entry_not_found(db: &dyn DataI) {
+    let entry = db.get_entry("nonexistent-id").await.unwrap();
+    assert!(entry.is_none());
+}
+
+/// Test that enclosures are stored and returned with entries.
+pub(super) async fn test_entry_enclosures(db: &dyn DataI) {
+    let feed = new_test_feed("Podcast", "https://podcast.example.com/feed.xml");
+    let enclosure = Enclosure {
+        url: "https://podcast.example.com/1.mp3".to_string(),
+        mime_type: Some("audio/mpeg".to_string()),
+        length: Some(1234),
+        duration_seconds: Some(3600),
+        image_url: None,
+    };
+    let entry = NewEntry {
+        enclosures: vec![enclosure.clone()],
+        ..new_test_entry("Episode 1", "https://podcast.example.com/1")
+    };
+
+    db.upsert_feed_and_entries_and_icon(&feed, vec![entry], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let result = db.query_entries(None, None).await.unwrap();
+    assert_eq!(result.entries[0].enclosures.0, vec![enclosure.clone()]);
+
+    let feed_entries = db
+        .get_feed_entries(&result.entries[0].feed_id, None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        feed_entries.entries[0].enclosures.0,
+        vec![enclosure.clone()]
+    );
+
+    let entry = db
+        .get_entry(&result.entries[0].id)
+        .await
+        .unwrap()
+        .expect("entry");
+    assert_eq!(entry.enclosures.0, vec![enclosure]);
+    assert_eq!(entry.playback_position_seconds, None);
+}
+
+/// Test saving and clearing the playback position of an entry.
+pub(super) async fn test_update_entry_playback_position(db: &dyn DataI) {
+    let feed = new_test_feed("Playback", "https://playback.example.com/feed.xml");
+    let entry = new_test_entry("Episode", "https://playback.example.com/1");
+    db.upsert_feed_and_entries_and_icon(&feed, vec![entry], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let entry_id = db.query_entries(None, None).await.unwrap().entries[0]
+        .id
+        .clone();
+
+    assert!(
+        db.update_entry_playback_position(&entry_id, Some(125))
+            .await
+            .unwrap()
+    );
+    let entry = db.get_entry(&entry_id).await.unwrap().expect("entry");
+    assert_eq!(entry.playback_position_seconds, Some(125));
+
+    assert!(
+        db.update_entry_playback_position(&entry_id, None)
+            .await
+            .unwrap()
+    );
+    let entry = db.get_entry(&entry_id).await.unwrap().expect("entry");
+    assert_eq!(entry.playback_position_seconds, None);
+
+    assert!(
+        !db.update_entry_playback_position("nonexistent-id", Some(1))
+            .await
+            .unwrap()
+    );
+}
+
+/// Test that extraction is attempted once per recent entry and its outcome is
+/// kept across upserts.
+pub(super) async fn test_entry_extraction(db: &dyn DataI) {
+    let feed = new_test_feed("Teasers", "https://teasers.example.com/feed.xml");
+    let entries = vec![
+        new_test_entry("First", "https://teasers.example.com/1"),
+        new_test_entry("Second", "https://teasers.example.com/2"),
+        // too old to be worth extracting
+        NewEntry {
+            published_at: Some(Utc::now() - Duration::days(30)),
+            ..new_test_entry("Archived", "https://teasers.example.com/archived")
+        },
+    ];
+    let week_ago = Utc::now() - Duration::days(7);
+    db.upsert_feed_and_entries_and_icon(&feed, entries.clone(), IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let feed_id = db.get_feeds_with_entry_counts().await.unwrap()[0]
+        .id
+        .clone();
+
+    let pending = db
+        .get_entries_to_extract(&feed_id, week_ago, 10)
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 2);
+    assert_eq!(
+        db.get_entries_to_extract(&feed_id, week_ago, 1)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let (first, second) = (&pending[0], &pending[1]);
+    db.set_entry_extraction(&first.id, Ok("<div><p>Full article</p></div>"))
+        .await
+        .unwrap();
+    db.set_entry_extraction(&second.id, Err("no article found in page"))
+        .await
+        .unwrap();
+
+    assert!(
+        db.get_entries_to_extract(&feed_id, week_ago, 10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // a later sync doesn't reset the outcome
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let extracted = db.get_entry(&first.id).await.unwrap().expect("entry");
+    assert_eq!(
+        extracted.extracted_content.as_deref(),
+        Some("<div><p>Full article</p></div>")
+    );
+    assert!(extracted.extracted_at.is_some());
+    assert_eq!(extracted.extraction_error, None);
+
+    let failed = db.get_entry(&second.id).await.unwrap().expect("entry");
+    assert_eq!(failed.extracted_content, None);
+    assert!(failed.extracted_at.is_some());
+    assert_eq!(
+        failed.extraction_error.as_deref(),
+        Some("no article found in page")
+    );
+}
+
+/// Test that upserting an existing entry updates its summary and content.
+pub(super) async fn test_upsert_entries_updates_content(db: &dyn DataI) {
+    let feed = new_test_feed(
+        "Content Update Feed",
+        "https://content-update.example.com/feed.xml",
+    );
+    let entry = NewEntry {
+        title: "Entry".to_string(),
+        url: "https://content-update.example.com/entry1".to_string(),
+        summary: Some("Old summary".to_string()),
+        summary_type: Some("html".to_string()),
+        ..Default::default()
+    };
+
+    db.upsert_feed_and_entries_and_icon(&feed, vec![entry], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let updated_entry = NewEntry {
+        title: "Entry".to_string(),
+        url: "https://content-update.example.com/entry1".to_string(),
+        summary: Some("New summary".to_string()),
+        summary_type: Some("html".to_string()),
+        content: Some("<p>Now with content</p>".to_string()),
+        content_type: Some("html".to_string()),
+        ..Default::default()
+    };
+
+    db.upsert_feed_and_entries_and_icon(&feed, vec![updated_entry], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let feeds = db.get_feeds_with_entry_counts().await.unwrap();
+    assert_eq!(feeds[0].entry_count, 1);
+
+    let entries = db.get_feed_entries(&feeds[0].id, None, None).await.unwrap();
+    let entry = db
+        .get_entry(&entries.entries[0].id)
+        .await
+        .unwrap()
+        .expect("entry");
+    assert_eq!(entry.summary, Some("New summary".to_string()));
+    assert_eq!(entry.content, Some("<p>Now with content</p>".to_string()));
+}
+
 // ----------------------------------------------------------------------------
 // Update feed tests
 // ----------------------------------------------------------------------------
@@ -954,7 +1448,7 @@ pub(super) async fn test_query_entries_filter_date_range(db: &dyn DataI) {
 pub(super) async fn test_update_feed(db: &dyn DataI) {
     // Create a feed first
     let feed = new_test_feed("Original Title", "https://original.example.com/feed.xml");
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -1008,7 +1502,7 @@ pub(super) async fn test_update_feed_not_found(db: &dyn DataI) {
 pub(super) async fn test_update_feed_clear_user_title(db: &dyn DataI) {
     // Create a feed
     let feed = new_test_feed("Source Title", "https://clear-title.example.com/feed.xml");
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -1053,6 +1547,90 @@ pub(super) async fn test_update_feed_clear_user_title(db: &dyn DataI) {
     assert!(feed.user_title.is_none());
 }
 
+/// Test toggling full content extraction for a feed.
+pub(super) async fn test_set_feed_fetch_full_content(db: &dyn DataI) {
+    let feed = new_test_feed("Full Content", "https://full-content.example.com/feed.xml");
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let feed_id = db.get_feeds_with_entry_counts().await.unwrap()[0]
+        .id
+        .clone();
+    assert!(
+        !db.get_one_feed_to_sync(&feed_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .fetch_full_content
+    );
+
+    db.set_feed_fetch_full_content(&feed_id, true)
+        .await
+        .unwrap();
+
+    let feed = db
+        .get_feed_by_id_with_entry_counts(&feed_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(feed.fetch_full_content);
+
+    assert!(
+        db.set_feed_fetch_full_content("nonexistent-id", true)
+            .await
+            .is_err()
+    );
+}
+
+/// Test overriding robots.txt for a feed.
+pub(super) async fn test_set_feed_ignore_robots(db: &dyn DataI) {
+    let feed_url = "https://robots.example.com/feed.xml";
+    let feed = new_test_feed("Robots", feed_url);
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let feed_id = db.get_feeds_with_entry_counts().await.unwrap()[0]
+        .id
+        .clone();
+    assert!(
+        !db.get_one_feed_to_sync(&feed_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .ignore_robots
+    );
+
+    db.set_feed_ignore_robots(feed_url, true).await.unwrap();
+
+    let feed = db
+        .get_feed_by_id_with_entry_counts(&feed_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(feed.ignore_robots);
+
+    // syncing the feed again keeps the override
+    let feed = new_test_feed("Robots", feed_url);
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
+        .await
+        .unwrap();
+    assert!(
+        db.get_one_feed_to_sync(&feed_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .ignore_robots
+    );
+
+    assert!(
+        db.set_feed_ignore_robots("https://nonexistent.example.com/feed.xml", true)
+            .await
+            .is_err()
+    );
+}
+
 // ----------------------------------------------------------------------------
 // Delete feed tests
 // ----------------------------------------------------------------------------
@@ -1061,7 +1639,7 @@ pub(super) async fn test_update_feed_clear_user_title(db: &dyn DataI) {
 pub(super) async fn test_delete_feed(db: &dyn DataI) {
     // Create a feed first
     let feed = new_test_feed("Delete Test Feed", "https://delete.example.com/feed.xml");
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -1098,7 +1676,7 @@ pub(super) async fn test_delete_feed_cascades_entries(db: &dyn DataI) {
         new_test_entry("Entry 3", "https://cascade.example.com/entry3"),
     ];
 
-    db.upsert_feed_and_entries_and_icon(&feed, entries, None)
+    db.upsert_feed_and_entries_and_icon(&feed, entries, IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -1134,7 +1712,7 @@ pub(super) async fn test_get_feeds_to_sync_empty(db: &dyn DataI) {
 /// Test that get_feeds_to_sync returns a stale feed.
 pub(super) async fn test_get_feeds_to_sync_returns_stale(db: &dyn DataI) {
     let feed = new_test_feed("Sync Feed", "https://sync.example.com/feed.xml");
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -1153,7 +1731,7 @@ pub(super) async fn test_set_feed_sync_result(db: &dyn DataI) {
         "Sync Result Feed",
         "https://sync-result.example.com/feed.xml",
     );
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -1172,7 +1750,7 @@ pub(super) async fn test_set_feed_sync_result(db: &dyn DataI) {
 /// Test get_one_feed_to_sync for existing and missing feeds.
 pub(super) async fn test_get_one_feed_to_sync(db: &dyn DataI) {
     let feed = new_test_feed("One Sync Feed", "https://one-sync.example.com/feed.xml");
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -1191,7 +1769,7 @@ pub(super) async fn test_get_one_feed_to_sync(db: &dyn DataI) {
 /// Test get_similar_named_feed returns a match.
 pub(super) async fn test_get_similar_named_feed(db: &dyn DataI) {
     let feed = new_test_feed("Similar Feed", "https://similar.example.com/feed.xml");
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -1209,7 +1787,7 @@ pub(super) async fn test_get_similar_named_feed(db: &dyn DataI) {
 /// Test get_similar_named_feed returns None when no match exists.
 pub(super) async fn test_get_similar_named_feed_no_match(db: &dyn DataI) {
     let feed = new_test_feed("Some Feed", "https://somefeed.example.com/feed.xml");
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -1220,41 +1798,58 @@ pub(super) async fn test_get_similar_named_feed_no_match(db: &dyn DataI) {
     assert!(result.is_none());
 }
 
-/// Test that get_feeds_to_sync excludes feeds with parse_error.
-pub(super) async fn test_get_feeds_to_sync_excludes_parse_error(db: &dyn DataI) {
-    // Create a feed
+/// Test that feeds that failed to parse are still retried.
+pub(super) async fn test_get_feeds_to_sync_retries_parse_error(db: &dyn DataI) {
     let feed = new_test_feed(
         "Parse Error Feed",
         "https://parse-error.example.com/feed.xml",
     );
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
-    // Set the sync result to parse_error
     db.set_feed_sync_result("https://parse-error.example.com/feed.xml", "parse_error")
         .await
         .unwrap();
 
-    // The feed should be excluded from sync even though it's stale
     let feeds_to_sync = db
         .get_feeds_to_sync(Utc::now() + Duration::hours(1))
         .await
         .unwrap();
 
-    assert!(feeds_to_sync.is_empty());
+    assert_eq!(feeds_to_sync.len(), 1);
+    assert_eq!(feeds_to_sync[0].consecutive_failures, 1);
+}
 
-    // If we change the sync result to something else, it should be included
-    db.set_feed_sync_result("https://parse-error.example.com/feed.xml", "network_error")
+/// Test counting failed syncs in a row.
+pub(super) async fn test_set_feed_sync_result_counts_failures(db: &dyn DataI) {
+    let feed_url = "https://failing.example.com/feed.xml";
+    let feed = new_test_feed("Failing Feed", feed_url);
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
+    let feed_id = db.get_feeds_with_entry_counts().await.unwrap()[0]
+        .id
+        .clone();
 
-    let feeds_to_sync = db
-        .get_feeds_to_sync(Utc::now() + Duration::hours(1))
+    db.set_feed_sync_result(feed_url, "fetch_error")
+        .await
+        .unwrap();
+    db.set_feed_sync_result(feed_url, "parse_error")
         .await
         .unwrap();
+    let to_sync = db.get_one_feed_to_sync(&feed_id).await.unwrap().unwrap();
+    assert_eq!(to_sync.consecutive_failures, 2);
 
-    assert_eq!(feeds_to_sync.len(), 1);
+    // failing for less than a week isn't broken yet
+    let feeds = db.get_feeds_with_entry_counts().await.unwrap();
+    assert!(!feeds[0].broken);
+
+    db.set_feed_sync_result(feed_url, "not_modified")
+        .await
+        .unwrap();
+    let to_sync = db.get_one_feed_to_sync(&feed_id).await.unwrap().unwrap();
+    assert_eq!(to_sync.consecutive_failures, 0);
 }
 
 /// Test that get_feeds_to_sync handles sync timeout correctly.
@@ -1265,7 +1860,7 @@ pub(super) async fn test_get_feeds_to_sync_respects_sync_timeout(db: &dyn DataI)
         "Sync Timeout Feed",
         "https://sync-timeout.example.com/feed.xml",
     );
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], None)
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
         .await
         .unwrap();
 
@@ -1297,6 +1892,152 @@ pub(super) async fn test_get_feeds_to_sync_respects_sync_timeout(db: &dyn DataI)
     assert_eq!(feeds_to_sync.len(), 1);
 }
 
+/// Test that a scheduled feed isn't synced before it's due.
+pub(super) async fn test_schedule_feed_sync(db: &dyn DataI) {
+    let feed_url = "https://scheduled.example.com/feed.xml";
+    let feed = new_test_feed("Scheduled Feed", feed_url);
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    // never scheduled: due an hour after the last sync
+    let next_sync_at = db.get_next_sync_at().await.unwrap().unwrap();
+    assert!(next_sync_at > Utc::now() + Duration::minutes(59));
+    assert!(next_sync_at < Utc::now() + Duration::minutes(61));
+
+    let interval = std::time::Duration::from_secs(3 * 60 * 60);
+    db.schedule_feed_sync(feed_url, interval, interval)
+        .await
+        .unwrap();
+
+    let next_sync_at = db.get_next_sync_at().await.unwrap().unwrap();
+    assert!(next_sync_at > Utc::now() + Duration::minutes(179));
+    assert!(next_sync_at < Utc::now() + Duration::minutes(181));
+
+    let feeds_to_sync = db
+        .get_feeds_to_sync(Utc::now() + Duration::hours(2))
+        .await
+        .unwrap();
+    assert!(feeds_to_sync.is_empty());
+
+    let feeds_to_sync = db
+        .get_feeds_to_sync(Utc::now() + Duration::hours(4))
+        .await
+        .unwrap();
+    assert_eq!(feeds_to_sync.len(), 1);
+    assert_eq!(feeds_to_sync[0].sync_interval_seconds, Some(3 * 60 * 60));
+}
+
+/// Test that get_next_sync_at waits out syncs in progress.
+pub(super) async fn test_get_next_sync_at(db: &dyn DataI) {
+    assert!(db.get_next_sync_at().await.unwrap().is_none());
+
+    let feed_url = "https://next-sync.example.com/feed.xml";
+    let feed = new_test_feed("Next Sync Feed", feed_url);
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
+        .await
+        .unwrap();
+    assert!(db.get_next_sync_at().await.unwrap().is_some());
+
+    // the sync may be given up on after five minutes
+    let feeds_to_sync = db
+        .get_feeds_to_sync(Utc::now() + Duration::hours(2))
+        .await
+        .unwrap();
+    assert_eq!(feeds_to_sync.len(), 1);
+    let next_sync_at = db.get_next_sync_at().await.unwrap().unwrap();
+    assert!(next_sync_at > Utc::now() + Duration::minutes(4));
+    assert!(next_sync_at <= Utc::now() + Duration::minutes(5));
+}
+
+/// Test that gone feeds aren't synced any more.
+pub(super) async fn test_get_feeds_to_sync_excludes_gone(db: &dyn DataI) {
+    let feed_url = "https://gone.example.com/feed.xml";
+    let feed = new_test_feed("Gone Feed", feed_url);
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    db.set_feed_sync_result(feed_url, "gone").await.unwrap();
+
+    let feeds_to_sync = db
+        .get_feeds_to_sync(Utc::now() + Duration::hours(2))
+        .await
+        .unwrap();
+    assert!(feeds_to_sync.is_empty());
+    assert!(db.get_next_sync_at().await.unwrap().is_none());
+}
+
+/// Test counting permanent redirects to the same place in a row.
+pub(super) async fn test_record_feed_redirect(db: &dyn DataI) {
+    let feed_url = "https://redirecting.example.com/feed.xml";
+    let feed = new_test_feed("Redirecting Feed", feed_url);
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
+        .await
+        .unwrap();
+
+    let new_url = Some("https://redirecting.example.com/rss");
+    assert_eq!(db.record_feed_redirect(feed_url, new_url).await.unwrap(), 1);
+    assert_eq!(db.record_feed_redirect(feed_url, new_url).await.unwrap(), 2);
+    assert_eq!(
+        db.record_feed_redirect(feed_url, Some("https://elsewhere.example.com/rss"))
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(db.record_feed_redirect(feed_url, None).await.unwrap(), 0);
+
+    assert!(
+        db.record_feed_redirect("https://missing.example.com/feed.xml", None)
+            .await
+            .is_err()
+    );
+}
+
+/// Test that moving a feed keeps its entries and the unique feed URLs.
+pub(super) async fn test_move_feed(db: &dyn DataI) {
+    let feed_url = "https://moving.example.com/feed.xml";
+    let new_url = "https://moved.example.com/feed.xml";
+    let feed = new_test_feed("Moving Feed", feed_url);
+    db.upsert_feed_and_entries_and_icon(
+        &feed,
+        vec![new_test_entry("Entry", "https://moving.example.com/entry")],
+        IconLookup::Skipped,
+    )
+    .await
+    .unwrap();
+    let feed_id = db.get_feeds_with_entry_counts().await.unwrap()[0]
+        .id
+        .clone();
+
+    assert!(db.move_feed(feed_url, new_url).await.unwrap());
+
+    let moved = db
+        .get_feed_by_id_with_entry_counts(&feed_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(moved.feed_url, new_url);
+    let entries = db.get_feed_entries(&feed_id, None, None).await.unwrap();
+    assert_eq!(entries.entries.len(), 1);
+
+    // the new url keeps being synced into the same feed
+    let feed = new_test_feed("Moving Feed", new_url);
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Skipped)
+        .await
+        .unwrap();
+    assert_eq!(db.get_feeds_with_entry_counts().await.unwrap().len(), 1);
+
+    // a feed can't move onto another one
+    let other_url = "https://other.example.com/feed.xml";
+    let other = new_test_feed("Other Feed", other_url);
+    db.upsert_feed_and_entries_and_icon(&other, vec![], IconLookup::Skipped)
+        .await
+        .unwrap();
+    assert!(!db.move_feed(other_url, new_url).await.unwrap());
+    assert_eq!(db.get_feeds_with_entry_counts().await.unwrap().len(), 2);
+}
+
 // ----------------------------------------------------------------------------
 // Icon tests
 // ----------------------------------------------------------------------------
@@ -1319,7 +2060,7 @@ pub(super) async fn test_upsert_icon(db: &dyn DataI) {
     db.upsert_feed_and_entries_and_icon(
         &feed,
         vec![],
-        Some(NewIcon {
+        IconLookup::Found(NewIcon {
             hash: icon_hash,
             data: icon_data,
             content_type: icon_content_type,
@@ -1345,7 +2086,7 @@ pub(super) async fn test_icon_deduplication_by_hash(db: &dyn DataI) {
     db.upsert_feed_and_entries_and_icon(
         &feed1,
         vec![],
-        Some(NewIcon {
+        IconLookup::Found(NewIcon {
             hash: "shared_hash_123".to_string(),
             data: vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A],
             content_type: "image/png".to_string(),
@@ -1362,7 +2103,7 @@ pub(super) async fn test_icon_deduplication_by_hash(db: &dyn DataI) {
     db.upsert_feed_and_entries_and_icon(
         &feed2,
         vec![],
-        Some(NewIcon {
+        IconLookup::Found(NewIcon {
             hash: "shared_hash_123".to_string(),
             data: vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A],
             content_type: "image/png".to_string(),
@@ -1403,6 +2144,74 @@ pub(super) async fn test_icon_deduplication_by_hash(db: &dyn DataI) {
     assert_eq!(icon1.hash, "shared_hash_123");
 }
 
+/// Test caching proxied images by url.
+pub(super) async fn test_proxied_image_cache(db: &dyn DataI) {
+    let url = format!("https://images.example.com/{}.png", "a".repeat(4000));
+    assert!(db.get_proxied_image(&url).await.unwrap().is_none());
+
+    db.upsert_proxied_image(&NewProxiedImage {
+        url: url.clone(),
+        content_type: "image/png".to_string(),
+        data: vec![0x89, 0x50, 0x4E, 0x47],
+    })
+    .await
+    .unwrap();
+
+    let cached = db.get_proxied_image(&url).await.unwrap().expect("cached");
+    assert_eq!(cached.content_type, "image/png");
+    assert_eq!(cached.data, vec![0x89, 0x50, 0x4E, 0x47]);
+
+    // refetching replaces the cached copy
+    db.upsert_proxied_image(&NewProxiedImage {
+        url: url.clone(),
+        content_type: "image/webp".to_string(),
+        data: vec![0x52, 0x49, 0x46, 0x46],
+    })
+    .await
+    .unwrap();
+
+    let refreshed = db.get_proxied_image(&url).await.unwrap().expect("cached");
+    assert_eq!(refreshed.content_type, "image/webp");
+    assert!(refreshed.fetched_at >= cached.fetched_at);
+
+    assert!(
+        db.get_proxied_image("https://images.example.com/other.png")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+/// Test deleting old proxied images and the least recently fetched ones over
+/// the size limit.
+pub(super) async fn test_prune_proxied_images(db: &dyn DataI) {
+    for name in ["oldest", "older", "newest"] {
+        db.upsert_proxied_image(&NewProxiedImage {
+            url: format!("https://images.example.com/{name}.png"),
+            content_type: "image/png".to_string(),
+            data: vec![0; 4],
+        })
+        .await
+        .unwrap();
+    }
+    let cached = |name: &str| {
+        let url = format!("https://images.example.com/{name}.png");
+        async move { db.get_proxied_image(&url).await.unwrap().is_some() }
+    };
+
+    let day_ago = Utc::now() - Duration::days(1);
+    assert_eq!(db.prune_proxied_images(day_ago, 12).await.unwrap(), 0);
+
+    assert_eq!(db.prune_proxied_images(day_ago, 8).await.unwrap(), 1);
+    assert!(!cached("oldest").await);
+    assert!(cached("older").await);
+    assert!(cached("newest").await);
+
+    let soon = Utc::now() + Duration::minutes(1);
+    assert_eq!(db.prune_proxied_images(soon, 8).await.unwrap(), 2);
+    assert!(!cached("newest").await);
+}
+
 /// Test updating a feed's icon to a different one.
 pub(super) async fn test_feed_icon_update(db: &dyn DataI) {
     let feed = new_test_feed(
@@ -1417,7 +2226,7 @@ pub(super) async fn test_feed_icon_update(db: &dyn DataI) {
         content_type: "image/png".to_string(),
     };
 
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], Some(initial_icon))
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Found(initial_icon))
         .await
         .unwrap();
 
@@ -1434,17 +2243,18 @@ pub(super) async fn test_feed_icon_update(db: &dyn DataI) {
         content_type: "image/jpeg".to_string(),
     };
 
-    db.upsert_feed_and_entries_and_icon(&feed, vec![], Some(new_icon))
+    db.upsert_feed_and_entries_and_icon(&feed, vec![], IconLookup::Found(new_icon))
         .await
         .unwrap();
 
-    // Feed should now have the new icon
-    // Note: The current implementation adds a new feed_icon association
-    // but the query returns any icon associated with the feed.
-    // This test verifies the upsert doesn't fail.
+    // Feed should now have the new icon, replacing the old one
     let feeds = db.get_feeds_with_entry_counts().await.unwrap();
     assert_eq!(feeds.len(), 1);
     assert!(feeds[0].has_icon);
+
+    let icon = db.get_icon_by_feed_id(&feed_id).await.unwrap().unwrap();
+    assert_eq!(icon.hash, "new_icon_hash");
+    assert_eq!(icon.content_type, "image/jpeg");
 }
 
 // ----------------------------------------------------------------------------
@@ -8,21 +8,29 @@ use crate::db::pg::test_utils::TestDb;
 use super::{
     test_create_feed, test_create_feed_with_icon, test_create_feed_without_entries,
     test_create_opml_import_job, test_delete_feed, test_delete_feed_cascades_entries,
-    test_delete_feed_not_found, test_feed_icon_update, test_get_existing_feed_urls,
-    test_get_existing_feed_urls_empty, test_get_feed_by_id, test_get_feed_by_id_not_found,
-    test_get_feed_entries_cursor, test_get_feed_entries_cursor_left, test_get_feed_entries_empty,
-    test_get_feed_entries_limit, test_get_feeds_empty, test_get_feeds_to_sync_empty,
-    test_get_feeds_to_sync_excludes_parse_error, test_get_feeds_to_sync_respects_sync_timeout,
-    test_get_feeds_to_sync_returns_stale, test_get_one_feed_to_sync,
+    test_delete_feed_not_found, test_entry_enclosures, test_entry_extraction,
+    test_feed_icon_update, test_get_entry_not_found, test_get_entry_with_content,
+    test_get_existing_feed_urls, test_get_existing_feed_urls_empty, test_get_feed_by_id,
+    test_get_feed_by_id_not_found, test_get_feed_entries_cursor, test_get_feed_entries_cursor_left,
+    test_get_feed_entries_empty, test_get_feed_entries_limit, test_get_feeds_empty,
+    test_get_feeds_to_sync_empty, test_get_feeds_to_sync_excludes_gone,
+    test_get_feeds_to_sync_respects_sync_timeout, test_get_feeds_to_sync_retries_parse_error,
+    test_get_feeds_to_sync_returns_stale, test_get_next_sync_at, test_get_one_feed_to_sync,
     test_get_opml_import_job_not_found, test_get_opml_import_recent_items,
     test_get_similar_named_feed, test_get_similar_named_feed_no_match,
-    test_icon_deduplication_by_hash, test_insert_stub_feeds, test_query_entries_cursor_pagination,
-    test_query_entries_empty, test_query_entries_filter_date_range,
+    test_icon_deduplication_by_hash, test_icon_refreshed_at, test_insert_stub_feeds,
+    test_move_feed, test_proxied_image_cache, test_prune_proxied_images,
+    test_query_entries_cursor_pagination, test_query_entries_empty,
+    test_query_entries_filter_author_and_category, test_query_entries_filter_date_range,
     test_query_entries_filter_feed_id, test_query_entries_filter_query_search,
     test_query_entries_filter_sort_and_limit, test_query_entries_filter_starred,
-    test_query_entries_filter_unread, test_query_entries_no_filters, test_set_feed_sync_result,
-    test_update_feed, test_update_feed_clear_user_title, test_update_feed_not_found,
-    test_update_opml_import_item_and_job_status, test_upsert_entries,
+    test_query_entries_filter_unread, test_query_entries_no_filters, test_record_feed_redirect,
+    test_schedule_feed_sync, test_set_feed_fetch_full_content, test_set_feed_ignore_robots,
+    test_set_feed_sync_result, test_set_feed_sync_result_counts_failures,
+    test_update_entry_playback_position, test_update_feed, test_update_feed_clear_user_title,
+    test_update_feed_not_found, test_update_opml_import_item_and_job_status, test_upsert_entries,
+    test_upsert_entries_adopts_guid, test_upsert_entries_keyed_by_guid,
+    test_upsert_entries_sharing_url, test_upsert_entries_updates_content,
     test_upsert_entries_updates_existing, test_upsert_feed_deduplicates_entries,
     test_upsert_feed_updates_existing, test_upsert_icon,
 };
@@ -55,6 +63,12 @@ async fn pg_create_feed_with_icon() {
     test_create_feed_with_icon(&*test_db.data).await;
 }
 
+#[tokio::test]
+async fn pg_icon_refreshed_at() {
+    let test_db = TestDb::new().await;
+    test_icon_refreshed_at(&*test_db.data).await;
+}
+
 // ----------------------------------------------------------------------------
 // Upsert behavior tests
 // ----------------------------------------------------------------------------
@@ -77,6 +91,24 @@ async fn pg_upsert_feed_deduplicates_entries() {
     test_upsert_feed_deduplicates_entries(&*test_db.data).await;
 }
 
+#[tokio::test]
+async fn pg_upsert_entries_keyed_by_guid() {
+    let test_db = TestDb::new().await;
+    test_upsert_entries_keyed_by_guid(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_upsert_entries_sharing_url() {
+    let test_db = TestDb::new().await;
+    test_upsert_entries_sharing_url(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_upsert_entries_adopts_guid() {
+    let test_db = TestDb::new().await;
+    test_upsert_entries_adopts_guid(&*test_db.data).await;
+}
+
 // ----------------------------------------------------------------------------
 // Read feed tests
 // ----------------------------------------------------------------------------
@@ -181,6 +213,12 @@ async fn pg_query_entries_filter_starred() {
     test_query_entries_filter_starred(&*test_db.data).await;
 }
 
+#[tokio::test]
+async fn pg_query_entries_filter_author_and_category() {
+    let test_db = TestDb::new().await;
+    test_query_entries_filter_author_and_category(&*test_db.data).await;
+}
+
 #[tokio::test]
 async fn pg_query_entries_filter_query_search() {
     let test_db = TestDb::new().await;
@@ -193,6 +231,42 @@ async fn pg_query_entries_filter_date_range() {
     test_query_entries_filter_date_range(&*test_db.data).await;
 }
 
+#[tokio::test]
+async fn pg_get_entry_with_content() {
+    let test_db = TestDb::new().await;
+    test_get_entry_with_content(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_get_entry_not_found() {
+    let test_db = TestDb::new().await;
+    test_get_entry_not_found(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_upsert_entries_updates_content() {
+    let test_db = TestDb::new().await;
+    test_upsert_entries_updates_content(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_entry_enclosures() {
+    let test_db = TestDb::new().await;
+    test_entry_enclosures(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_update_entry_playback_position() {
+    let test_db = TestDb::new().await;
+    test_update_entry_playback_position(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_entry_extraction() {
+    let test_db = TestDb::new().await;
+    test_entry_extraction(&*test_db.data).await;
+}
+
 // ----------------------------------------------------------------------------
 // Update feed tests
 // ----------------------------------------------------------------------------
@@ -215,6 +289,18 @@ async fn pg_update_feed_clear_user_title() {
     test_update_feed_clear_user_title(&*test_db.data).await;
 }
 
+#[tokio::test]
+async fn pg_set_feed_fetch_full_content() {
+    let test_db = TestDb::new().await;
+    test_set_feed_fetch_full_content(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_set_feed_ignore_robots() {
+    let test_db = TestDb::new().await;
+    test_set_feed_ignore_robots(&*test_db.data).await;
+}
+
 // ----------------------------------------------------------------------------
 // Delete feed tests
 // ----------------------------------------------------------------------------
@@ -278,9 +364,9 @@ async fn pg_get_similar_named_feed_no_match() {
 }
 
 #[tokio::test]
-async fn pg_get_feeds_to_sync_excludes_parse_error() {
+async fn pg_get_feeds_to_sync_retries_parse_error() {
     let test_db = TestDb::new().await;
-    test_get_feeds_to_sync_excludes_parse_error(&*test_db.data).await;
+    test_get_feeds_to_sync_retries_parse_error(&*test_db.data).await;
 }
 
 #[tokio::test]
@@ -289,6 +375,42 @@ async fn pg_get_feeds_to_sync_respects_sync_timeout() {
     test_get_feeds_to_sync_respects_sync_timeout(&*test_db.data).await;
 }
 
+#[tokio::test]
+async fn pg_set_feed_sync_result_counts_failures() {
+    let test_db = TestDb::new().await;
+    test_set_feed_sync_result_counts_failures(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_get_feeds_to_sync_excludes_gone() {
+    let test_db = TestDb::new().await;
+    test_get_feeds_to_sync_excludes_gone(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_record_feed_redirect() {
+    let test_db = TestDb::new().await;
+    test_record_feed_redirect(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_move_feed() {
+    let test_db = TestDb::new().await;
+    test_move_feed(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_schedule_feed_sync() {
+    let test_db = TestDb::new().await;
+    test_schedule_feed_sync(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_get_next_sync_at() {
+    let test_db = TestDb::new().await;
+    test_get_next_sync_at(&*test_db.data).await;
+}
+
 // ----------------------------------------------------------------------------
 // Icon tests
 // ----------------------------------------------------------------------------
@@ -305,6 +427,18 @@ async fn pg_icon_deduplication_by_hash() {
     test_icon_deduplication_by_hash(&*test_db.data).await;
 }
 
+#[tokio::test]
+async fn pg_proxied_image_cache() {
+    let test_db = TestDb::new().await;
+    test_proxied_image_cache(&*test_db.data).await;
+}
+
+#[tokio::test]
+async fn pg_prune_proxied_images() {
+    let test_db = TestDb::new().await;
+    test_prune_proxied_images(&*test_db.data).await;
+}
+
 #[tokio::test]
 async fn pg_feed_icon_update() {
     let test_db = TestDb::new().await;
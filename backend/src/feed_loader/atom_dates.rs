@@ -0,0 +1,126 @@
+use std::{borrow::Cow, ops::Range};
+
+use quick_xml::{Reader, escape::unescape, events::Event};
+
+/// `published` and `updated` of an Atom entry, as written in the feed.
+#[derive(Debug, Default, PartialEq)]
+pub struct RawEntryDates {
+    pub published: Option<String>,
+    pub updated: Option<String>,
+}
+
+/// Takes the `published` and `updated` elements of the feed, its entries and
+/// their sources out of an Atom document, since a single date the Atom parser
+/// can't read fails the whole feed. Returns the document without them and the
+/// raw dates of every `entry`, in document order, to be parsed leniently
+/// instead. A document that can't be read is returned as is.
+pub fn strip_atom_dates(bytes: &[u8]) -> (Cow<'_, [u8]>, Vec<RawEntryDates>) {
+    let mut reader = Reader::from_reader(bytes);
+
+    let mut entries: Vec<RawEntryDates> = Vec::new();
+    let mut removed: Vec<Range<usize>> = Vec::new();
+    // local names of the open elements
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        let (element, is_empty) = match reader.read_event() {
+            Ok(Event::Start(element)) => (element, false),
+            Ok(Event::Empty(element)) => (element, true),
+            Ok(Event::End(_)) => {
+                stack.pop();
+                continue;
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => return (Cow::Borrowed(bytes), Vec::new()),
+            _ => continue,
+        };
+
+        let name = element.local_name().as_ref().to_vec();
+        let parent = stack.last().map(Vec::as_slice);
+
+        match (name.as_slice(), parent) {
+            (b"entry", Some(b"feed")) if stack.len() == 1 => entries.push(RawEntryDates::default()),
+            (b"published" | b"updated", Some(b"feed" | b"entry" | b"source")) => {
+                let text = if is_empty {
+                    None
+                } else {
+                    match reader.read_text(element.name()) {
+                        Ok(text) => Some(
+                            unescape(&text)
+                                .map_or_else(|_| text.to_string(), |text| text.into_owned()),
+                        ),
+                        Err(_) => return (Cow::Borrowed(bytes), Vec::new()),
+                    }
+                };
+                removed.push(start..reader.buffer_position() as usize);
+
+                if parent == Some(b"entry")
+                    && stack.len() == 2
+                    && let Some(dates) = entries.last_mut()
+                {
+                    if name == b"published" {
+                        dates.published = text;
+                    } else {
+                        dates.updated = text;
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        if !is_empty {
+            stack.push(name);
+        }
+    }
+
+    if removed.is_empty() {
+        return (Cow::Borrowed(bytes), entries);
+    }
+
+    let mut stripped = Vec::with_capacity(bytes.len());
+    let mut kept_from = 0;
+    for range in removed {
+        stripped.extend_from_slice(&bytes[kept_from..range.start]);
+        kept_from = range.end;
+    }
+    stripped.extend_from_slice(&bytes[kept_from..]);
+
+    (Cow::Owned(stripped), entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_dates_and_keeps_their_text() {
+        let document = br#"<feed xmlns="http://www.w3.org/2005/Atom">
+<updated>yesterday</updated>
+<entry><id>1</id><published>5 Jan 2026</published><updated>2026-01-06T10:00:00Z</updated></entry>
+<entry><id>2</id><source><updated>whenever</updated></source><updated/></entry>
+</feed>"#;
+
+        let (stripped, dates) = strip_atom_dates(document);
+
+        assert_eq!(
+            String::from_utf8_lossy(&stripped),
+            r#"<feed xmlns="http://www.w3.org/2005/Atom">
+
+<entry><id>1</id></entry>
+<entry><id>2</id><source></source></entry>
+</feed>"#
+        );
+        assert_eq!(
+            dates,
+            vec![
+                RawEntryDates {
+                    published: Some("5 Jan 2026".to_string()),
+                    updated: Some("2026-01-06T10:00:00Z".to_string()),
+                },
+                RawEntryDates::default(),
+            ]
+        );
+    }
+}
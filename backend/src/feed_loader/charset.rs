@@ -0,0 +1,339 @@
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252};
+
+/// How far into a document the XML declaration and `<meta charset>` are
+/// looked for.
+const SNIFF_LEN: usize = 4096;
+
+/// Transcodes a feed to UTF-8. The encoding comes from the BOM, then the XML
+/// declaration, then the `Content-Type` charset. The XML declaration is
+/// rewritten to say UTF-8 so parsers don't decode the document a second time.
+pub fn decode_feed(bytes: Vec<u8>, content_type: &str) -> Vec<u8> {
+    let declared = xml_declared_encoding(&bytes).or_else(|| {
+        content_type_encoding(content_type).map(|encoding| {
+            if utf_16_without_bom(&bytes).is_some() {
+                encoding
+            } else {
+                ascii_compatible(encoding)
+            }
+        })
+    });
+
+    let decoded = decode(bytes, declared);
+    rewrite_xml_declaration(decoded)
+}
+
+/// Transcodes an HTML page to UTF-8. The encoding comes from the BOM, then the
+/// `Content-Type` charset, then `<meta charset>`, as browsers do.
+pub fn decode_html(bytes: Vec<u8>, content_type: &str) -> Vec<u8> {
+    let declared = content_type_encoding(content_type).or_else(|| meta_charset(&bytes));
+
+    decode(bytes, declared)
+}
+
+fn decode(bytes: Vec<u8>, declared: Option<&'static Encoding>) -> Vec<u8> {
+    let (encoding, bom_len) = match Encoding::for_bom(&bytes) {
+        Some((encoding, bom_len)) => (encoding, bom_len),
+        None => (
+            declared
+                .or_else(|| utf_16_without_bom(&bytes))
+                .unwrap_or_else(|| undeclared_encoding(&bytes)),
+            0,
+        ),
+    };
+
+    if encoding == UTF_8 && bom_len == 0 {
+        return bytes;
+    }
+
+    let (decoded, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+    if had_errors {
+        tracing::debug!(encoding = encoding.name(), "invalid bytes while decoding");
+    }
+    decoded.into_owned().into_bytes()
+}
+
+/// Undeclared documents are UTF-8, unless they can't be, in which case
+/// windows-1252 (what ISO-8859-1 labels map to) is the likeliest.
+fn undeclared_encoding(bytes: &[u8]) -> &'static Encoding {
+    if std::str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else {
+        WINDOWS_1252
+    }
+}
+
+/// UTF-16 documents without a BOM still start with `<`.
+fn utf_16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    match bytes {
+        [b'<', 0, ..] => Some(UTF_16LE),
+        [0, b'<', ..] => Some(UTF_16BE),
+        _ => None,
+    }
+}
+
+fn content_type_encoding(content_type: &str) -> Option<&'static Encoding> {
+    content_type
+        .split(';')
+        .skip(1)
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        .and_then(|(_, value)| encoding_for_label(value))
+}
+
+fn xml_declared_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+    let (start, end) = xml_declaration(bytes)?;
+    let declaration = &bytes[start..end];
+
+    let value_start = find(declaration, b"encoding")? + b"encoding".len();
+    let value = declaration[value_start..]
+        .strip_prefix(b"=")
+        .or_else(|| {
+            let rest = declaration[value_start..].trim_ascii_start();
+            rest.strip_prefix(b"=")
+        })?
+        .trim_ascii_start();
+    let quote = *value.first()?;
+    if quote != b'"' && quote != b'\'' {
+        return None;
+    }
+    let value = &value[1..];
+    let len = value.iter().position(|b| *b == quote)?;
+
+    Encoding::for_label(&value[..len]).map(ascii_compatible)
+}
+
+/// Start and end of `<?xml ... ?>` at the start of an ASCII-compatible
+/// document.
+fn xml_declaration(bytes: &[u8]) -> Option<(usize, usize)> {
+    let head = &bytes[..bytes.len().min(SNIFF_LEN)];
+    let head_start = head.len() - head.trim_ascii_start().len();
+    let head_start = head_start
+        + if head[head_start..].starts_with(b"\xEF\xBB\xBF") {
+            3
+        } else {
+            0
+        };
+
+    if !head[head_start..].starts_with(b"<?xml") {
+        return None;
+    }
+    let end = head_start + find(&head[head_start..], b"?>")?;
+    Some((head_start, end))
+}
+
+/// Replaces the `encoding` of the XML declaration with UTF-8, now that the
+/// document has been transcoded.
+fn rewrite_xml_declaration(bytes: Vec<u8>) -> Vec<u8> {
+    let Some((start, end)) = xml_declaration(&bytes) else {
+        return bytes;
+    };
+    let Some(encoding_start) = find(&bytes[start..end], b"encoding").map(|i| start + i) else {
+        return bytes;
+    };
+
+    let mut rewritten = Vec::with_capacity(bytes.len());
+    rewritten.extend_from_slice(&bytes[..encoding_start]);
+    rewritten.extend_from_slice(b"encoding=\"utf-8\"");
+
+    // skip the old `encoding = "..."`
+    let rest = &bytes[encoding_start + b"encoding".len()..end];
+    let skipped = rest
+        .iter()
+        .position(|b| *b == b'"' || *b == b'\'')
+        .and_then(|open| {
+            let quote = rest[open];
+            rest[open + 1..]
+                .iter()
+                .position(|b| *b == quote)
+                .map(|close| open + close + 2)
+        })
+        .unwrap_or(rest.len());
+    rewritten.extend_from_slice(&rest[skipped..]);
+    rewritten.extend_from_slice(&bytes[end..]);
+
+    rewritten
+}
+
+/// `<meta charset="...">` or `<meta http-equiv="Content-Type" content="...;
+/// charset=...">` near the start of the page.
+fn meta_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head = bytes[..bytes.len().min(SNIFF_LEN)].to_ascii_lowercase();
+
+    let mut rest = head.as_slice();
+    while let Some(start) = find(rest, b"<meta") {
+        let tag = &rest[start..];
+        let tag = &tag[..tag.iter().position(|b| *b == b'>').unwrap_or(tag.len())];
+
+        if let Some(charset) = find(tag, b"charset") {
+            let value = tag[charset + b"charset".len()..]
+                .trim_ascii_start()
+                .strip_prefix(b"=")
+                .map(|value| value.trim_ascii_start())
+                .map(|value| value.strip_prefix(b"\"").unwrap_or(value))
+                .map(|value| value.strip_prefix(b"'").unwrap_or(value));
+
+            if let Some(value) = value {
+                let len = value
+                    .iter()
+                    .position(|b| {
+                        matches!(b, b'"' | b'\'' | b';' | b'/') || b.is_ascii_whitespace()
+                    })
+                    .unwrap_or(value.len());
+
+                if let Some(encoding) = Encoding::for_label(&value[..len]) {
+                    return Some(ascii_compatible(encoding));
+                }
+            }
+        }
+
+        rest = &rest[start + b"<meta".len()..];
+    }
+
+    None
+}
+
+/// A document that could declare its encoding in ASCII isn't UTF-16, whatever
+/// the declaration says.
+fn ascii_compatible(encoding: &'static Encoding) -> &'static Encoding {
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        UTF_8
+    } else {
+        encoding
+    }
+}
+
+fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().trim_matches(['"', '\'']).as_bytes())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOWS_1251: &[u8] = include_bytes!("fixtures/rss_windows_1251.xml");
+    const SHIFT_JIS: &[u8] = include_bytes!("fixtures/page_shift_jis.html");
+
+    fn text(bytes: Vec<u8>) -> String {
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn decodes_feed_using_xml_declaration() {
+        let decoded = text(decode_feed(WINDOWS_1251.to_vec(), "application/rss+xml"));
+
+        assert!(decoded.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>"));
+        assert!(decoded.contains("<title>Новости</title>"));
+    }
+
+    #[test]
+    fn transcoded_feed_parses() {
+        let decoded = decode_feed(WINDOWS_1251.to_vec(), "");
+        let url = url::Url::parse("https://news.example.ru/rss").unwrap();
+
+        let (feed, entries) = crate::feed_loader::feed::parse_feed(&decoded, &url).unwrap();
+        assert_eq!(feed.title, "Новости");
+        assert_eq!(entries[0].title, "Первая новость");
+    }
+
+    #[test]
+    fn decodes_feed_using_content_type() {
+        let bytes = b"<rss><channel><title>Caf\xe9</title></channel></rss>".to_vec();
+
+        let decoded = text(decode_feed(bytes, "text/xml; charset=ISO-8859-1"));
+        assert_eq!(decoded, "<rss><channel><title>Café</title></channel></rss>");
+    }
+
+    #[test]
+    fn xml_declaration_wins_over_content_type() {
+        let decoded = text(decode_feed(
+            WINDOWS_1251.to_vec(),
+            "text/xml; charset=utf-8",
+        ));
+        assert!(decoded.contains("Новости"));
+    }
+
+    #[test]
+    fn bom_wins_over_declaration() {
+        let mut bytes = b"\xEF\xBB\xBF<?xml version='1.0' encoding='windows-1251'?>".to_vec();
+        bytes.extend_from_slice("<title>Café</title>".as_bytes());
+
+        let decoded = text(decode_feed(bytes, ""));
+        assert_eq!(
+            decoded,
+            "<?xml version='1.0' encoding=\"utf-8\"?><title>Café</title>"
+        );
+    }
+
+    #[test]
+    fn decodes_utf_16_feed() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "<?xml version=\"1.0\" encoding=\"UTF-16\"?><t>é</t>".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+
+        let decoded = text(decode_feed(bytes, ""));
+        assert_eq!(
+            decoded,
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?><t>é</t>"
+        );
+    }
+
+    #[test]
+    fn ignores_utf_16_declared_in_ascii() {
+        let bytes = "<?xml version=\"1.0\" encoding=\"UTF-16\"?><t>é</t>"
+            .as_bytes()
+            .to_vec();
+        let decoded = text(decode_feed(bytes, ""));
+        assert_eq!(
+            decoded,
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?><t>é</t>"
+        );
+
+        let bytes = "<t>é</t>".as_bytes().to_vec();
+        let decoded = text(decode_feed(bytes, "application/rss+xml; charset=utf-16"));
+        assert_eq!(decoded, "<t>é</t>");
+    }
+
+    #[test]
+    fn leaves_utf_8_untouched() {
+        let bytes = "<?xml version=\"1.0\"?><t>日本</t>".as_bytes().to_vec();
+        assert_eq!(decode_feed(bytes.clone(), "text/xml"), bytes);
+    }
+
+    #[test]
+    fn falls_back_to_windows_1252_for_invalid_utf_8() {
+        let decoded = text(decode_feed(b"<t>\x93quoted\x94</t>".to_vec(), ""));
+        assert_eq!(decoded, "<t>\u{201c}quoted\u{201d}</t>");
+    }
+
+    #[test]
+    fn decodes_html_using_meta_charset() {
+        let decoded = text(decode_html(SHIFT_JIS.to_vec(), "text/html"));
+        assert!(decoded.contains("<title>日本語のページ</title>"));
+    }
+
+    #[test]
+    fn decodes_html_using_http_equiv() {
+        let bytes =
+            b"<head><meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1251\"><title>\xcd\xee\xe2\xee\xf1\xf2\xe8</title>"
+                .to_vec();
+
+        assert!(text(decode_html(bytes, "text/html")).contains("Новости"));
+    }
+
+    #[test]
+    fn content_type_wins_over_meta_charset() {
+        let bytes = "<meta charset=\"shift_jis\"><title>Café</title>"
+            .as_bytes()
+            .to_vec();
+
+        let decoded = text(decode_html(bytes, "text/html; charset=utf-8"));
+        assert!(decoded.contains("Café"));
+    }
+}
@@ -0,0 +1,303 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+
+/// Formats tried after the value has been normalized: the day name is dropped,
+/// commas are removed and named or `GMT+2` style zones become numeric offsets.
+const ZONED_FORMATS: &[&str] = &[
+    "%d %b %y %H:%M:%S %z",
+    "%d %b %Y %H:%M:%S %z",
+    "%d %b %y %H:%M %z",
+    "%d %b %Y %H:%M %z",
+    "%d %B %Y %H:%M:%S %z",
+    "%d %B %Y %H:%M %z",
+    "%b %d %Y %H:%M:%S %z",
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S%.f%z",
+    "%Y-%m-%dT%H:%M%z",
+    "%Y-%m-%d %H:%M%z",
+    "%Y-%m-%d %H:%M:%S%.f %z",
+];
+
+/// Same shapes as [`ZONED_FORMATS`] without an offset, assumed to be UTC.
+const NAIVE_FORMATS: &[&str] = &[
+    "%d %b %y %H:%M:%S",
+    "%d %b %Y %H:%M:%S",
+    "%d %b %y %H:%M",
+    "%d %b %Y %H:%M",
+    "%d %B %Y %H:%M:%S",
+    "%b %d %Y %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Bare dates, taken as midnight UTC.
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d", "%Y/%m/%d", "%d %b %y", "%d %b %Y", "%d %B %Y", "%b %d %Y", "%B %d %Y",
+];
+
+const WEEKDAYS: &[&str] = &[
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Parses a feed date in whatever format the feed happened to use: RFC 2822
+/// (with named zones, `GMT+2` offsets or two-digit years), RFC 3339 and
+/// looser ISO 8601 variants, and bare dates. Values without a zone are
+/// taken as UTC. Returns `None` rather than failing for anything else.
+pub fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.to_utc());
+    }
+    if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+        return Some(date.to_utc());
+    }
+
+    let normalized = normalize(value);
+
+    ZONED_FORMATS
+        .iter()
+        .find_map(|format| DateTime::parse_from_str(&normalized, format).ok())
+        .map(|date| date.to_utc())
+        .or_else(|| {
+            NAIVE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(&normalized, format).ok())
+                .map(|date| date.and_utc())
+        })
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(&normalized, format).ok())
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc())
+        })
+}
+
+/// Parses the first candidate that holds a recognizable date, e.g. an RSS
+/// `pubDate` followed by its `dc:date` values. Candidates that can't be
+/// parsed are recorded in `diagnostics`.
+pub fn parse_first<'a>(
+    candidates: impl IntoIterator<Item = &'a str>,
+    diagnostics: &mut Vec<String>,
+) -> Option<DateTime<Utc>> {
+    for candidate in candidates {
+        if candidate.trim().is_empty() {
+            continue;
+        }
+        match parse_date(candidate) {
+            Some(date) => return Some(date),
+            None => diagnostics.push(format!("unparseable date {candidate:?}")),
+        }
+    }
+    None
+}
+
+fn normalize(value: &str) -> String {
+    let value = value.replace(',', " ");
+    let mut tokens: Vec<String> = value.split_whitespace().map(str::to_owned).collect();
+
+    if tokens.first().is_some_and(|token| is_weekday(token)) {
+        tokens.remove(0);
+    }
+
+    if let Some(last) = tokens.last_mut() {
+        if let Some(offset) = zone_offset(last) {
+            *last = format_offset(offset);
+        } else if let Some(stripped) = last.strip_suffix(['Z', 'z'])
+            && stripped.contains(':')
+        {
+            // 2026-01-05T09:30Z, which isn't valid RFC 3339 without seconds
+            *last = format!("{stripped}+0000");
+        }
+    }
+
+    tokens.join(" ")
+}
+
+fn is_weekday(token: &str) -> bool {
+    let token = token.trim_end_matches('.').to_ascii_lowercase();
+    token.len() >= 3 && WEEKDAYS.iter().any(|day| day.starts_with(&token))
+}
+
+/// Offset of a named zone (`EST`, `CEST`) or a `GMT+2` / `UTC-05:30` style zone.
+fn zone_offset(token: &str) -> Option<FixedOffset> {
+    let upper = token.to_ascii_uppercase();
+
+    let hours = match upper.as_str() {
+        "UT" | "UTC" | "GMT" | "Z" | "WET" => Some(0.0),
+        "BST" | "CET" | "WEST" => Some(1.0),
+        "CEST" | "EET" | "SAST" => Some(2.0),
+        "EEST" | "MSK" => Some(3.0),
+        "IST" => Some(5.5),
+        "SGT" | "AWST" | "HKT" => Some(8.0),
+        "JST" | "KST" => Some(9.0),
+        "ACST" => Some(9.5),
+        "AEST" => Some(10.0),
+        "ACDT" => Some(10.5),
+        "AEDT" => Some(11.0),
+        "NZST" => Some(12.0),
+        "NZDT" => Some(13.0),
+        "ADT" => Some(-3.0),
+        "AST" | "EDT" => Some(-4.0),
+        "EST" | "CDT" => Some(-5.0),
+        "CST" | "MDT" => Some(-6.0),
+        "MST" | "PDT" => Some(-7.0),
+        "PST" | "AKDT" => Some(-8.0),
+        "AKST" => Some(-9.0),
+        "HST" => Some(-10.0),
+        _ => None,
+    };
+    if let Some(hours) = hours {
+        return FixedOffset::east_opt((hours * 3600.0) as i32);
+    }
+
+    let rest = upper
+        .strip_prefix("GMT")
+        .or_else(|| upper.strip_prefix("UTC"))?;
+    let (sign, rest) = match rest.as_bytes().first()? {
+        b'+' => (1, &rest[1..]),
+        b'-' => (-1, &rest[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if rest.len() <= 2 => (rest, "0"),
+        None if rest.len() == 4 => rest.split_at(2),
+        None => return None,
+    };
+    let hours: i32 = hours.parse().ok().filter(|hours| *hours <= 23)?;
+    let minutes: i32 = minutes.parse().ok().filter(|minutes| *minutes <= 59)?;
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+fn format_offset(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{sign}{:02}{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32, s: u32) -> Option<DateTime<Utc>> {
+        Some(Utc.with_ymd_and_hms(y, m, d, h, min, s).unwrap())
+    }
+
+    #[test]
+    fn parses_rfc_2822() {
+        assert_eq!(
+            parse_date("Mon, 05 Jan 2026 09:30:00 +0000"),
+            utc(2026, 1, 5, 9, 30, 0)
+        );
+        assert_eq!(
+            parse_date("Mon, 5 Jan 2026 09:30:00 GMT"),
+            utc(2026, 1, 5, 9, 30, 0)
+        );
+    }
+
+    #[test]
+    fn parses_named_zones() {
+        assert_eq!(
+            parse_date("Mon, 05 Jan 2026 09:30:00 EST"),
+            utc(2026, 1, 5, 14, 30, 0)
+        );
+        assert_eq!(
+            parse_date("Mon, 05 Jan 2026 09:30:00 CEST"),
+            utc(2026, 1, 5, 7, 30, 0)
+        );
+        assert_eq!(
+            parse_date("05 Jan 2026 09:30:00 GMT+2"),
+            utc(2026, 1, 5, 7, 30, 0)
+        );
+        assert_eq!(
+            parse_date("05 Jan 2026 09:30:00 UTC-05:30"),
+            utc(2026, 1, 5, 15, 0, 0)
+        );
+    }
+
+    #[test]
+    fn parses_sloppy_rfc_822() {
+        // wrong day name
+        assert_eq!(
+            parse_date("Tue, 05 Jan 2026 09:30:00 +0000"),
+            utc(2026, 1, 5, 9, 30, 0)
+        );
+        // full day and month names
+        assert_eq!(
+            parse_date("Monday, 5 January 2026 09:30:00 +0000"),
+            utc(2026, 1, 5, 9, 30, 0)
+        );
+        assert_eq!(
+            parse_date("Mon, 05 Jan 26 09:30 +0000"),
+            utc(2026, 1, 5, 9, 30, 0)
+        );
+        assert_eq!(
+            parse_date("Mon, 05 Jan 2026 09:30:00"),
+            utc(2026, 1, 5, 9, 30, 0)
+        );
+    }
+
+    #[test]
+    fn parses_iso_8601() {
+        assert_eq!(
+            parse_date("2026-01-05T09:30:00Z"),
+            utc(2026, 1, 5, 9, 30, 0)
+        );
+        assert_eq!(
+            parse_date("2026-01-05T11:30:00.123+02:00").map(|date| date.timestamp()),
+            utc(2026, 1, 5, 9, 30, 0).map(|date| date.timestamp())
+        );
+        assert_eq!(
+            parse_date("2026-01-05T11:30:00+0200"),
+            utc(2026, 1, 5, 9, 30, 0)
+        );
+        assert_eq!(
+            parse_date("2026-01-05 09:30:00 UTC"),
+            utc(2026, 1, 5, 9, 30, 0)
+        );
+        assert_eq!(parse_date("2026-01-05T09:30Z"), utc(2026, 1, 5, 9, 30, 0));
+        assert_eq!(parse_date("2026-01-05T09:30:00"), utc(2026, 1, 5, 9, 30, 0));
+    }
+
+    #[test]
+    fn parses_bare_dates() {
+        assert_eq!(parse_date("2026-01-05"), utc(2026, 1, 5, 0, 0, 0));
+        assert_eq!(parse_date("5 January 2026"), utc(2026, 1, 5, 0, 0, 0));
+        assert_eq!(parse_date("January 5, 2026"), utc(2026, 1, 5, 0, 0, 0));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert_eq!(parse_date(""), None);
+        assert_eq!(parse_date("yesterday"), None);
+        assert_eq!(parse_date("32 Jan 2026 09:30:00 +0000"), None);
+        assert_eq!(parse_date("Mon, 05 Jan 2026 09:30:00 XYZ"), None);
+        assert_eq!(parse_date("Mon, 05 Jan 2026 09:30:00 GMT+9999999:00"), None);
+        assert_eq!(parse_date("Mon, 05 Jan 2026 09:30:00 UTC+02:75"), None);
+    }
+
+    #[test]
+    fn parse_first_falls_back_and_records_diagnostics() {
+        let mut diagnostics = Vec::new();
+        let date = parse_first(["not a date", "", "2026-01-05"], &mut diagnostics);
+
+        assert_eq!(date, utc(2026, 1, 5, 0, 0, 0));
+        assert_eq!(diagnostics, vec!["unparseable date \"not a date\""]);
+    }
+}
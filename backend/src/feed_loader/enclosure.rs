@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+
+use crate::db::Enclosure;
+
+/// Enclosures of an RSS item: `<enclosure>`, `media:content` (bare or inside
+/// `media:group`) and `itunes:duration`/`itunes:image`.
+pub fn rss_enclosures(item: &rss::Item) -> Vec<Enclosure> {
+    let mut enclosures = Vec::new();
+
+    if let Some(enclosure) = &item.enclosure {
+        push(
+            &mut enclosures,
+            Enclosure {
+                url: enclosure.url.trim().to_owned(),
+                mime_type: non_empty(&enclosure.mime_type),
+                length: parse_length(&enclosure.length),
+                duration_seconds: None,
+                image_url: None,
+            },
+        );
+    }
+
+    media_enclosures(&item.extensions, &mut enclosures);
+
+    if let Some(itunes) = &item.itunes_ext {
+        let duration = itunes.duration.as_deref().and_then(parse_duration);
+        let image = itunes.image.as_deref().and_then(non_empty);
+
+        // iTunes metadata describes the episode, i.e. the main enclosure
+        if let Some(first) = enclosures.first_mut() {
+            first.duration_seconds = first.duration_seconds.or(duration);
+            first.image_url = first.image_url.take().or(image);
+        }
+    }
+
+    enclosures
+}
+
+/// Enclosures of an Atom entry: `rel="enclosure"` links and `media:content`.
+pub fn atom_enclosures(entry: &atom_syndication::Entry) -> Vec<Enclosure> {
+    let mut enclosures = Vec::new();
+
+    for link in entry.links.iter().filter(|link| link.rel == "enclosure") {
+        push(
+            &mut enclosures,
+            Enclosure {
+                url: link.href.trim().to_owned(),
+                mime_type: link.mime_type.as_deref().and_then(non_empty),
+                length: link.length.as_deref().and_then(parse_length),
+                duration_seconds: None,
+                image_url: None,
+            },
+        );
+    }
+
+    media_enclosures(&entry.extensions, &mut enclosures);
+
+    enclosures
+}
+
+/// Parses `itunes:duration`, which is either a number of seconds or
+/// `[HH:]MM:SS`.
+pub fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    value.split(':').try_fold(0i64, |total, part| {
+        let part: f64 = part.trim().parse().ok()?;
+        (part >= 0.0).then(|| total * 60 + part as i64)
+    })
+}
+
+/// The `ExtensionMap`s of the rss and atom crates have the same shape but
+/// distinct types.
+trait Extension: Sized {
+    fn attr(&self, name: &str) -> Option<&str>;
+    fn children(&self) -> &BTreeMap<String, Vec<Self>>;
+}
+
+impl Extension for rss::extension::Extension {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.get(name).map(String::as_str)
+    }
+    fn children(&self) -> &BTreeMap<String, Vec<Self>> {
+        &self.children
+    }
+}
+
+impl Extension for atom_syndication::extension::Extension {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.get(name).map(String::as_str)
+    }
+    fn children(&self) -> &BTreeMap<String, Vec<Self>> {
+        &self.children
+    }
+}
+
+fn media_enclosures<E: Extension>(
+    extensions: &BTreeMap<String, BTreeMap<String, Vec<E>>>,
+    enclosures: &mut Vec<Enclosure>,
+) {
+    let Some(media) = extensions.get("media") else {
+        return;
+    };
+
+    let item_thumbnail = media.get("thumbnail").and_then(|t| thumbnail(t));
+
+    let groups = media
+        .get("group")
+        .into_iter()
+        .flatten()
+        .map(|group| group.children());
+
+    for elements in std::iter::once(media).chain(groups) {
+        let group_thumbnail = elements.get("thumbnail").and_then(|t| thumbnail(t));
+
+        for content in elements.get("content").into_iter().flatten() {
+            let Some(url) = content.attr("url").and_then(non_empty) else {
+                continue;
+            };
+
+            push(
+                enclosures,
+                Enclosure {
+                    url,
+                    mime_type: content.attr("type").and_then(non_empty),
+                    length: content.attr("fileSize").and_then(parse_length),
+                    duration_seconds: content.attr("duration").and_then(parse_duration),
+                    image_url: content
+                        .children()
+                        .get("thumbnail")
+                        .and_then(|t| thumbnail(t))
+                        .or_else(|| group_thumbnail.clone())
+                        .or_else(|| item_thumbnail.clone()),
+                },
+            );
+        }
+    }
+}
+
+fn thumbnail<E: Extension>(thumbnails: &[E]) -> Option<String> {
+    thumbnails
+        .iter()
+        .find_map(|thumbnail| thumbnail.attr("url").and_then(non_empty))
+}
+
+/// Adds the enclosure, or fills in what's missing from an earlier one with
+/// the same URL (e.g. `<enclosure>` repeated as `media:content`).
+fn push(enclosures: &mut Vec<Enclosure>, enclosure: Enclosure) {
+    if enclosure.url.is_empty() {
+        return;
+    }
+
+    match enclosures.iter_mut().find(|e| e.url == enclosure.url) {
+        Some(existing) => {
+            existing.mime_type = existing.mime_type.take().or(enclosure.mime_type);
+            existing.length = existing.length.or(enclosure.length);
+            existing.duration_seconds = existing.duration_seconds.or(enclosure.duration_seconds);
+            existing.image_url = existing.image_url.take().or(enclosure.image_url);
+        }
+        None => enclosures.push(enclosure),
+    }
+}
+
+/// Lengths of 0 are commonly used for "unknown".
+fn parse_length(value: &str) -> Option<i64> {
+    value.trim().parse().ok().filter(|length| *length > 0)
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PODCAST: &[u8] = include_bytes!("fixtures/podcast.xml");
+    const ATOM_MEDIA: &[u8] = include_bytes!("fixtures/atom_media.xml");
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("3600"), Some(3600));
+        assert_eq!(parse_duration("1:02:03"), Some(3723));
+        assert_eq!(parse_duration("45:30"), Some(2730));
+        assert_eq!(parse_duration("61.5"), Some(61));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("an hour"), None);
+        assert_eq!(parse_duration("-5"), None);
+    }
+
+    #[test]
+    fn parses_podcast_enclosures() {
+        let channel = rss::Channel::read_from(PODCAST).unwrap();
+
+        let first = rss_enclosures(&channel.items[0]);
+        assert_eq!(
+            first,
+            vec![Enclosure {
+                url: "https://cast.example.com/episodes/2.mp3".to_string(),
+                mime_type: Some("audio/mpeg".to_string()),
+                length: Some(34567890),
+                duration_seconds: Some(3723),
+                image_url: Some("https://cast.example.com/episodes/2.jpg".to_string()),
+            }]
+        );
+
+        // media:content only, with a group thumbnail
+        let second = rss_enclosures(&channel.items[1]);
+        assert_eq!(
+            second,
+            vec![
+                Enclosure {
+                    url: "https://cast.example.com/episodes/1.mp4".to_string(),
+                    mime_type: Some("video/mp4".to_string()),
+                    length: None,
+                    duration_seconds: Some(1800),
+                    image_url: Some("https://cast.example.com/episodes/1.jpg".to_string()),
+                },
+                Enclosure {
+                    url: "https://cast.example.com/episodes/1.webm".to_string(),
+                    mime_type: Some("video/webm".to_string()),
+                    length: Some(1000),
+                    duration_seconds: None,
+                    image_url: Some("https://cast.example.com/episodes/1.jpg".to_string()),
+                },
+            ]
+        );
+
+        assert!(rss_enclosures(&channel.items[2]).is_empty());
+    }
+
+    #[test]
+    fn parses_atom_enclosures() {
+        let feed = atom_syndication::Feed::read_from(ATOM_MEDIA).unwrap();
+
+        assert_eq!(
+            atom_enclosures(&feed.entries[0]),
+            vec![Enclosure {
+                url: "https://video.example.com/v/abc.mp4".to_string(),
+                mime_type: Some("video/mp4".to_string()),
+                length: Some(2048),
+                duration_seconds: Some(90),
+                image_url: Some("https://video.example.com/v/abc.jpg".to_string()),
+            }]
+        );
+    }
+}
@@ -0,0 +1,451 @@
+use std::{collections::HashMap, rc::Rc};
+
+use html5ever::{
+    ParseOpts, parse_document,
+    serialize::{SerializeOpts, TraversalScope, serialize},
+    tendril::TendrilSink,
+};
+use markup5ever_rcdom::{Handle, Node, NodeData, RcDom, SerializableHandle};
+use url::Url;
+
+use crate::feed_loader::xml_base::resolve;
+
+/// Paragraphs shorter than this don't count towards their container's score.
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// Below this much text the best candidate is more likely a teaser or a
+/// cookie wall than the article.
+const MIN_ARTICLE_CHARS: usize = 250;
+
+/// Extracts the main content of an article page, readability-style: scores
+/// the containers of paragraph-like elements by how much prose they hold and
+/// keeps the best one, plus siblings that look like part of the same article.
+/// Links and images in the result are made absolute against the page.
+pub fn extract_article(bytes: &[u8], page_url: &Url) -> Option<String> {
+    let dom = parse_document(RcDom::default(), ParseOpts::default())
+        .from_utf8()
+        .read_from(&mut &*bytes)
+        .ok()?;
+
+    let base = base_url(&dom.document, page_url);
+    remove_unlikely(&dom.document);
+
+    let mut scorer = Scorer::default();
+    scorer.score(&dom.document, &mut Vec::new());
+
+    let (top, top_score) = scorer.top_candidate()?;
+    let parts = scorer.article_parts(&top, top_score);
+
+    let text_len: usize = parts.iter().map(text_len).sum();
+    if text_len < MIN_ARTICLE_CHARS {
+        return None;
+    }
+
+    let mut html = String::from("<div>");
+    for part in &parts {
+        absolutize_urls(part, &base);
+        html.push_str(&serialize_node(part)?);
+    }
+    html.push_str("</div>");
+
+    Some(html)
+}
+
+#[derive(Default)]
+struct Scorer {
+    /// Content score of every candidate container, keyed by node address.
+    scores: HashMap<*const Node, (Handle, f64)>,
+    parents: HashMap<*const Node, Handle>,
+}
+
+impl Scorer {
+    fn score(&mut self, node: &Handle, ancestors: &mut Vec<Handle>) {
+        if let Some(parent) = ancestors.last() {
+            self.parents.insert(Rc::as_ptr(node), parent.clone());
+        }
+
+        if is_paragraph(node) {
+            let text = inner_text(node);
+            let len = text.trim().chars().count();
+
+            if len >= MIN_PARAGRAPH_CHARS {
+                let commas = text.matches([',', '，', '、']).count();
+                let score = 1.0 + commas as f64 + (len as f64 / 100.0).min(3.0);
+
+                // the parent gets the full score, further ancestors less
+                for (level, ancestor) in ancestors.iter().rev().take(5).enumerate() {
+                    if !matches!(ancestor.data, NodeData::Element { .. }) {
+                        break;
+                    }
+                    let divider = match level {
+                        0 => 1.0,
+                        1 => 2.0,
+                        level => level as f64 * 3.0,
+                    };
+                    let (_, candidate_score) = self
+                        .scores
+                        .entry(Rc::as_ptr(ancestor))
+                        .or_insert_with(|| (ancestor.clone(), initial_score(ancestor)));
+                    *candidate_score += score / divider;
+                }
+            }
+        }
+
+        ancestors.push(node.clone());
+        for child in node.children.borrow().iter() {
+            self.score(child, ancestors);
+        }
+        ancestors.pop();
+    }
+
+    fn top_candidate(&self) -> Option<(Handle, f64)> {
+        self.scores
+            .values()
+            .map(|(node, score)| (node.clone(), score * (1.0 - link_density(node))))
+            .filter(|(node, _)| !matches!(element_name(node), Some("html" | "body")))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .or_else(|| {
+                let (body, _) = self
+                    .scores
+                    .values()
+                    .find(|(node, _)| element_name(node) == Some("body"))?;
+                Some((body.clone(), 0.0))
+            })
+    }
+
+    /// The top candidate along with siblings that are likely part of the same
+    /// article, e.g. an intro paragraph outside the main container.
+    fn article_parts(&self, top: &Handle, top_score: f64) -> Vec<Handle> {
+        let Some(parent) = self.parents.get(&Rc::as_ptr(top)) else {
+            return vec![top.clone()];
+        };
+
+        let threshold = (top_score * 0.2).max(10.0);
+        let top_class = attr(top, "class");
+
+        parent
+            .children
+            .borrow()
+            .iter()
+            .filter(|sibling| {
+                if Rc::ptr_eq(sibling, top) {
+                    return true;
+                }
+                if !matches!(sibling.data, NodeData::Element { .. }) {
+                    return false;
+                }
+
+                let bonus = match (&top_class, attr(sibling, "class")) {
+                    (Some(top_class), Some(class)) if !class.is_empty() && *top_class == class => {
+                        top_score * 0.2
+                    }
+                    _ => 0.0,
+                };
+                let score = self
+                    .scores
+                    .get(&Rc::as_ptr(sibling))
+                    .map_or(0.0, |(_, score)| *score);
+                if score + bonus >= threshold {
+                    return true;
+                }
+
+                if element_name(sibling) == Some("p") {
+                    let text = inner_text(sibling);
+                    let len = text.trim().chars().count();
+                    let density = link_density(sibling);
+
+                    return (len > 80 && density < 0.25)
+                        || (len > 0 && density == 0.0 && text.contains(". "));
+                }
+
+                false
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+fn is_paragraph(node: &Handle) -> bool {
+    match element_name(node) {
+        Some("p" | "pre" | "td") => true,
+        // divs used as paragraphs
+        Some("div") => !node
+            .children
+            .borrow()
+            .iter()
+            .any(|child| element_name(child).is_some_and(|name| BLOCK_ELEMENTS.contains(&name))),
+        _ => false,
+    }
+}
+
+fn initial_score(node: &Handle) -> f64 {
+    let tag_score = match element_name(node) {
+        Some("article") => 10.0,
+        Some("div") => 5.0,
+        Some("pre" | "td" | "blockquote") => 3.0,
+        Some("address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form") => -3.0,
+        Some("h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th") => -5.0,
+        _ => 0.0,
+    };
+    tag_score + class_weight(node)
+}
+
+/// Class names and ids that hint at content or at page chrome.
+fn class_weight(node: &Handle) -> f64 {
+    ["class", "id"]
+        .into_iter()
+        .filter_map(|name| attr(node, name))
+        .map(|value| {
+            let value = value.to_ascii_lowercase();
+            let mut weight = 0.0;
+            if NEGATIVE_HINTS.iter().any(|hint| value.contains(hint)) {
+                weight -= 25.0;
+            }
+            if POSITIVE_HINTS.iter().any(|hint| value.contains(hint)) {
+                weight += 25.0;
+            }
+            weight
+        })
+        .sum()
+}
+
+/// Share of the node's text that is link text.
+fn link_density(node: &Handle) -> f64 {
+    let len = text_len(node);
+    if len == 0 {
+        return 0.0;
+    }
+
+    fn link_text_len(node: &Handle) -> usize {
+        if element_name(node) == Some("a") {
+            return text_len(node);
+        }
+        node.children.borrow().iter().map(link_text_len).sum()
+    }
+
+    link_text_len(node) as f64 / len as f64
+}
+
+/// Drops elements that are never part of an article: scripts, forms and
+/// navigation, and containers whose class or id marks them as page chrome.
+fn remove_unlikely(node: &Handle) {
+    node.children
+        .borrow_mut()
+        .retain(|child| match &child.data {
+            NodeData::Comment { .. } => false,
+            NodeData::Element { name, .. } => {
+                let name = name.local.as_ref();
+                if REMOVED_ELEMENTS.contains(&name) {
+                    return false;
+                }
+                if matches!(name, "html" | "body" | "article" | "main") {
+                    return true;
+                }
+
+                let hints = format!(
+                    "{} {}",
+                    attr(child, "class").unwrap_or_default(),
+                    attr(child, "id").unwrap_or_default()
+                )
+                .to_ascii_lowercase();
+                let unlikely = UNLIKELY_HINTS.iter().any(|hint| hints.contains(hint));
+                !unlikely || MAYBE_HINTS.iter().any(|hint| hints.contains(hint))
+            }
+            _ => true,
+        });
+
+    for child in node.children.borrow().iter() {
+        remove_unlikely(child);
+    }
+}
+
+fn absolutize_urls(node: &Handle, base: &Url) {
+    if let NodeData::Element { attrs, .. } = &node.data {
+        for attr in attrs.borrow_mut().iter_mut() {
+            if matches!(attr.name.local.as_ref(), "href" | "src" | "poster") {
+                let resolved = resolve(base, &attr.value);
+                attr.value = resolved.into();
+            }
+        }
+    }
+
+    for child in node.children.borrow().iter() {
+        absolutize_urls(child, base);
+    }
+}
+
+fn base_url(document: &Handle, page_url: &Url) -> Url {
+    fn find_base(node: &Handle) -> Option<String> {
+        if element_name(node) == Some("base") {
+            return attr(node, "href");
+        }
+        node.children.borrow().iter().find_map(find_base)
+    }
+
+    find_base(document)
+        .and_then(|href| page_url.join(href.trim()).ok())
+        .unwrap_or_else(|| page_url.to_owned())
+}
+
+fn serialize_node(node: &Handle) -> Option<String> {
+    let mut bytes = Vec::new();
+    serialize(
+        &mut bytes,
+        &SerializableHandle::from(node.clone()),
+        SerializeOpts {
+            traversal_scope: TraversalScope::IncludeNode,
+            ..Default::default()
+        },
+    )
+    .ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+fn inner_text(node: &Handle) -> String {
+    fn collect(node: &Handle, text: &mut String) {
+        if let NodeData::Text { contents } = &node.data {
+            text.push_str(&contents.borrow());
+        }
+        for child in node.children.borrow().iter() {
+            collect(child, text);
+        }
+    }
+
+    let mut text = String::new();
+    collect(node, &mut text);
+    text
+}
+
+fn text_len(node: &Handle) -> usize {
+    inner_text(node)
+        .split_whitespace()
+        .map(|w| w.chars().count())
+        .sum()
+}
+
+fn element_name(node: &Handle) -> Option<&str> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(name.local.as_ref()),
+        _ => None,
+    }
+}
+
+fn attr(node: &Handle, name: &str) -> Option<String> {
+    let NodeData::Element { attrs, .. } = &node.data else {
+        return None;
+    };
+    attrs
+        .borrow()
+        .iter()
+        .find(|attr| attr.name.local.as_ref() == name)
+        .map(|attr| attr.value.to_string())
+}
+
+const REMOVED_ELEMENTS: &[&str] = &[
+    "aside", "button", "footer", "form", "head", "input", "nav", "noscript", "script", "select",
+    "style", "template", "textarea",
+];
+
+const BLOCK_ELEMENTS: &[&str] = &[
+    "article",
+    "blockquote",
+    "div",
+    "dl",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+const UNLIKELY_HINTS: &[&str] = &[
+    "-ad-",
+    "banner",
+    "breadcrumb",
+    "combx",
+    "comment",
+    "community",
+    "cookie",
+    "disqus",
+    "footer",
+    "header",
+    "menu",
+    "modal",
+    "newsletter",
+    "pager",
+    "pagination",
+    "popup",
+    "related",
+    "remark",
+    "replies",
+    "share",
+    "shoutbox",
+    "sidebar",
+    "social",
+    "sponsor",
+    "subscribe",
+];
+
+const MAYBE_HINTS: &[&str] = &[
+    "and", "article", "body", "column", "content", "main", "post",
+];
+
+const NEGATIVE_HINTS: &[&str] = &[
+    "byline", "comment", "foot", "hidden", "meta", "nav", "promo", "related", "share", "sidebar",
+    "widget",
+];
+
+const POSITIVE_HINTS: &[&str] = &[
+    "article", "blog", "body", "content", "entry", "main", "page", "post", "story", "text",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE_PAGE: &[u8] = include_bytes!("fixtures/article_page.html");
+
+    fn extract(bytes: &[u8]) -> Option<String> {
+        extract_article(
+            bytes,
+            &Url::parse("https://blog.example.com/2026/05/article").unwrap(),
+        )
+    }
+
+    #[test]
+    fn extracts_main_content() {
+        let article = extract(ARTICLE_PAGE).expect("article");
+
+        assert!(article.contains("The first paragraph of the article"));
+        assert!(article.contains("The closing paragraph"));
+        // the intro sits outside the main container
+        assert!(article.contains("An introduction that precedes"));
+
+        assert!(!article.contains("Subscribe to our newsletter"));
+        assert!(!article.contains("Home"));
+        assert!(!article.contains("Great post!"));
+        assert!(!article.contains("trackVisit"));
+    }
+
+    #[test]
+    fn makes_urls_absolute() {
+        let article = extract(ARTICLE_PAGE).expect("article");
+
+        assert!(article.contains(r#"src="https://blog.example.com/images/chart.png""#));
+        assert!(article.contains(r#"href="https://blog.example.com/2026/05/other-post""#));
+    }
+
+    #[test]
+    fn gives_up_without_enough_text() {
+        let page = b"<html><body><nav><a href=/>Home</a></nav><p>Please enable JavaScript to continue.</p></body></html>";
+        assert_eq!(extract(page), None);
+    }
+}
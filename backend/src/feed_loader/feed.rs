@@ -1,14 +1,43 @@
-use chrono::DateTime;
+use std::time::Duration;
+
+use serde::Deserialize;
 use tracing::{debug, warn};
+use url::Url;
 
-use crate::db::NewEntry;
+use crate::{
+    db::{Enclosure, NewEntry},
+    feed_loader::{
+        atom_dates::strip_atom_dates,
+        date::parse_first,
+        enclosure::{atom_enclosures, rss_enclosures},
+        rdf::{is_rdf, parse_rdf},
+        sanitize::sanitize_entry,
+        schedule::{syndication_interval, ttl_interval},
+        title::with_fallback_title,
+        xml_base::{XmlBases, resolve},
+    },
+};
 
-pub fn parse_feed(bytes: &[u8], feed_url: &str) -> anyhow::Result<(ParsedFeed, Vec<NewEntry>)> {
-    debug!("parsing feed as RSS");
-    let feed = parse_rss(bytes).or_else(|_| {
-        debug!("failed to parse as RSS, parsing as Atom");
-        parse_atom(bytes, feed_url).map_err(|_| anyhow::anyhow!("failed to parse as Atom"))
-    })?;
+/// Relative URLs in the feed are resolved against `xml:base` and `base_url`,
+/// the URL the feed was fetched from.
+pub fn parse_feed(bytes: &[u8], base_url: &Url) -> anyhow::Result<(ParsedFeed, Vec<NewEntry>)> {
+    let feed = if looks_like_json(bytes) {
+        debug!("parsing feed as JSON Feed");
+        parse_json_feed(bytes, &XmlBases::document(base_url))?
+    } else {
+        let bases = XmlBases::read(bytes, base_url);
+        if is_rdf(bytes) {
+            debug!("parsing feed as RSS 1.0 (RDF)");
+            parse_rdf(bytes, &bases)?
+        } else {
+            debug!("parsing feed as RSS");
+            parse_rss(bytes, &bases).or_else(|_| {
+                debug!("failed to parse as RSS, parsing as Atom");
+                parse_atom(bytes, base_url, &bases)
+                    .map_err(|_| anyhow::anyhow!("failed to parse as Atom"))
+            })?
+        }
+    };
     debug!("parsed feed");
 
     // not using skipped for anything yet
@@ -17,116 +46,743 @@ pub fn parse_feed(bytes: &[u8], feed_url: &str) -> anyhow::Result<(ParsedFeed, V
 
 pub struct ParsedFeed {
     pub title: String,
+    pub format: FeedFormat,
     pub site_url: Option<String>,
+    /// The feed's own image or icon.
+    pub image_url: Option<String>,
+    /// How often the publisher asks to be polled at most, from RSS `<ttl>`
+    /// or `sy:updatePeriod`.
+    pub update_interval: Option<Duration>,
+    /// Problems that didn't prevent parsing, such as unparseable dates.
+    pub diagnostics: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedFormat {
+    Rss,
+    /// RSS 0.9 and 1.0
+    Rdf,
+    Atom,
+    Json,
 }
 
-fn parse_rss(bytes: &[u8]) -> anyhow::Result<(ParsedFeed, Vec<NewEntry>, usize)> {
+fn parse_rss(bytes: &[u8], bases: &XmlBases) -> anyhow::Result<(ParsedFeed, Vec<NewEntry>, usize)> {
     let parsed = rss::Channel::read_from(bytes)?;
-    let (entries, skipped) =
-        parsed
-            .items
-            .iter()
-            .fold((Vec::new(), 0usize), |(mut entries, mut skipped), item| {
-                let title = match &item.title {
-                    Some(title) => {
-                        if title.trim().is_empty() {
-                            warn!("title is empty for item {item:?}, skipping...");
-                            skipped += 1;
-                            return (entries, skipped);
-                        }
-                        title.to_string()
-                    }
-                    None => {
-                        warn!("no title found for item {item:?}, skipping...");
-                        skipped += 1;
-                        return (entries, skipped);
-                    }
-                };
+    let mut diagnostics = Vec::new();
+    let (entries, skipped) = parsed.items.iter().enumerate().fold(
+        (Vec::new(), 0usize),
+        |(mut entries, mut skipped), (index, item)| {
+            let url = match item.link.to_owned() {
+                Some(url) => url,
+                None => {
+                    warn!("no link found for item {item:?}, skipping...");
+                    skipped += 1;
+                    return (entries, skipped);
+                }
+            };
 
-                let url = match item.link.to_owned() {
-                    Some(url) => url,
-                    None => {
-                        warn!("no link found for item {item:?}, skipping...");
-                        skipped += 1;
-                        return (entries, skipped);
-                    }
-                };
+            let summary = non_empty(item.description.as_deref());
+            let content = non_empty(item.content.as_deref());
 
-                entries.push(NewEntry {
-                    title,
+            entries.push(resolve_entry(
+                bases.item(index),
+                with_fallback_title(NewEntry {
+                    title: item.title.to_owned().unwrap_or_default(),
                     url,
-                    published_at: item
-                        .pub_date
-                        .to_owned()
-                        .map(|date| DateTime::parse_from_rfc2822(&date).unwrap().into()),
+                    published_at: parse_first(
+                        item.pub_date.as_deref().into_iter().chain(
+                            item.dublin_core_ext
+                                .iter()
+                                .flat_map(|dc| dc.dates.iter().map(String::as_str)),
+                        ),
+                        &mut diagnostics,
+                    ),
                     comments_url: item
                         .comments
                         .to_owned()
                         .map(|comments| comments.to_string()),
                     // No per-entry updated_at timestamp in RSS 2.0
                     entry_updated_at: None,
-                });
+                    summary_type: summary.as_ref().map(|_| "html".to_string()),
+                    summary,
+                    content_type: content.as_ref().map(|_| "html".to_string()),
+                    content,
+                    authors: unique(
+                        item.author.as_deref().map(rss_author).into_iter().chain(
+                            item.dublin_core_ext
+                                .iter()
+                                .flat_map(|dc| dc.creators.iter().map(String::as_str)),
+                        ),
+                    ),
+                    categories: unique(
+                        item.categories
+                            .iter()
+                            .map(|category| category.name.as_str())
+                            .chain(
+                                item.dublin_core_ext
+                                    .iter()
+                                    .flat_map(|dc| dc.subjects.iter().map(String::as_str)),
+                            ),
+                    ),
+                    enclosures: rss_enclosures(item),
+                    guid: non_empty(item.guid.as_ref().map(|guid| guid.value.as_str())),
+                }),
+            ));
 
-                (entries, skipped)
-            });
+            (entries, skipped)
+        },
+    );
 
     Ok((
         ParsedFeed {
             title: parsed.title.to_string(),
-            site_url: Some(parsed.link.to_owned()),
+            format: FeedFormat::Rss,
+            site_url: non_empty(Some(&parsed.link)).map(|link| resolve(bases.feed(), &link)),
+            image_url: non_empty(parsed.image.as_ref().map(|image| image.url.as_str()))
+                .map(|url| resolve(bases.feed(), &url)),
+            update_interval: parsed.ttl.as_deref().and_then(ttl_interval).max(
+                parsed.syndication_ext.as_ref().and_then(|sy| {
+                    syndication_interval(&sy.period.to_string(), Some(&sy.frequency.to_string()))
+                }),
+            ),
+            diagnostics,
         },
         entries,
         skipped,
     ))
 }
 
-fn parse_atom(bytes: &[u8], feed_url: &str) -> anyhow::Result<(ParsedFeed, Vec<NewEntry>, usize)> {
-    let parsed = atom_syndication::Feed::read_from(bytes)?;
+fn parse_atom(
+    bytes: &[u8],
+    feed_url: &Url,
+    bases: &XmlBases,
+) -> anyhow::Result<(ParsedFeed, Vec<NewEntry>, usize)> {
+    let (bytes, dates) = strip_atom_dates(bytes);
+    let parsed = atom_syndication::Feed::read_from(&bytes[..])?;
 
-    let (entries, skipped) =
-        parsed
-            .entries
-            .iter()
-            .fold((Vec::new(), 0usize), |(mut entries, mut skipped), entry| {
-                let title = entry.title.to_owned().value.to_string();
-                if title.trim().is_empty() {
-                    warn!("title is empty for entry {entry:?}, skipping...");
+    let mut diagnostics = Vec::new();
+    let (entries, skipped) = parsed.entries.iter().enumerate().fold(
+        (Vec::new(), 0usize),
+        |(mut entries, mut skipped), (index, entry)| {
+            let url = match atom_entry_link(entry) {
+                Some(url) => url,
+                None => {
+                    warn!("no link found for entry {entry:?}, skipping...");
                     skipped += 1;
                     return (entries, skipped);
                 }
+            };
 
-                let url = match entry.links.first().map(|link| link.href.clone()) {
-                    Some(url) => url,
-                    None => {
-                        warn!("no link found for entry {entry:?}, skipping...");
-                        skipped += 1;
-                        return (entries, skipped);
-                    }
-                };
+            let summary = entry
+                .summary
+                .as_ref()
+                .filter(|summary| !summary.value.trim().is_empty());
+            let content = entry
+                .content
+                .as_ref()
+                .filter(|content| content.value.as_ref().is_some_and(|v| !v.trim().is_empty()));
+            let dates = dates.get(index);
 
-                entries.push(NewEntry {
-                    title,
+            entries.push(resolve_entry(
+                bases.item(index),
+                with_fallback_title(NewEntry {
+                    title: entry.title.value.to_owned(),
                     url,
-                    published_at: entry.published.map(|published| published.to_utc()),
+                    published_at: parse_first(
+                        dates.and_then(|dates| dates.published.as_deref()),
+                        &mut diagnostics,
+                    ),
                     comments_url: None,
-                    entry_updated_at: Some(entry.updated.to_utc()),
-                });
-                (entries, skipped)
-            });
+                    entry_updated_at: parse_first(
+                        dates.and_then(|dates| dates.updated.as_deref()),
+                        &mut diagnostics,
+                    ),
+                    summary: summary.map(|summary| summary.value.to_owned()),
+                    summary_type: summary.map(|summary| text_type(summary.r#type).to_string()),
+                    content: content.and_then(|content| content.value.to_owned()),
+                    content_type: content.map(|content| {
+                        content
+                            .content_type
+                            .to_owned()
+                            .unwrap_or_else(|| "text".to_string())
+                    }),
+                    // entries without their own author inherit the feed's
+                    authors: unique(
+                        if entry.authors.is_empty() {
+                            &parsed.authors
+                        } else {
+                            &entry.authors
+                        }
+                        .iter()
+                        .map(|person| person.name.as_str()),
+                    ),
+                    categories: unique(
+                        entry
+                            .categories
+                            .iter()
+                            .map(|category| category.label.as_deref().unwrap_or(&category.term)),
+                    ),
+                    enclosures: atom_enclosures(entry),
+                    guid: non_empty(Some(&entry.id)),
+                }),
+            ));
+            (entries, skipped)
+        },
+    );
 
-    let site_url = parsed
+    let links: Vec<_> = parsed
         .links
         .iter()
-        .find(|link| link.rel == "alternate")
-        .or(parsed.links.iter().find(|link| link.href != feed_url))
-        .map(|link| link.href.to_owned());
+        .map(|link| (link, resolve(bases.feed(), &link.href)))
+        .collect();
+    let site_url = links
+        .iter()
+        .find(|(link, _)| link.rel == "alternate")
+        .or(links.iter().find(|(_, href)| href != feed_url.as_str()))
+        .map(|(_, href)| href.to_owned());
 
     Ok((
         ParsedFeed {
             title: parsed.title.to_string(),
+            format: FeedFormat::Atom,
             site_url,
+            // icons are square, logos are usually wide
+            image_url: non_empty(parsed.icon.as_deref().or(parsed.logo.as_deref()))
+                .map(|url| resolve(bases.feed(), &url)),
+            update_interval: atom_update_interval(&parsed.extensions),
+            diagnostics,
         },
         entries,
         skipped,
     ))
 }
+
+/// `sy:updatePeriod` and `sy:updateFrequency`, which some Atom feeds carry
+/// too.
+fn atom_update_interval(
+    extensions: &atom_syndication::extension::ExtensionMap,
+) -> Option<Duration> {
+    let sy = extensions.get("sy")?;
+    let value = |name: &str| {
+        sy.get(name)?
+            .first()
+            .and_then(|extension| extension.value.as_deref())
+    };
+    syndication_interval(value("updatePeriod")?, value("updateFrequency"))
+}
+
+fn looks_like_json(bytes: &[u8]) -> bool {
+    strip_bom(bytes)
+        .iter()
+        .find(|byte| !byte.is_ascii_whitespace())
+        == Some(&b'{')
+}
+
+fn strip_bom(bytes: &[u8]) -> &[u8] {
+    bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes)
+}
+
+/// https://www.jsonfeed.org/version/1.1/
+#[derive(Deserialize)]
+struct JsonFeed {
+    version: String,
+    title: String,
+    home_page_url: Option<String>,
+    /// Small, for lists of feeds.
+    favicon: Option<String>,
+    /// Large, for the feed's own page.
+    icon: Option<String>,
+    #[serde(default)]
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Deserialize)]
+struct JsonFeedItem {
+    /// Should be a string, but some feeds use numbers.
+    id: Option<serde_json::Value>,
+    url: Option<String>,
+    external_url: Option<String>,
+    title: Option<String>,
+    content_html: Option<String>,
+    content_text: Option<String>,
+    summary: Option<String>,
+    date_published: Option<String>,
+    date_modified: Option<String>,
+    /// JSON Feed 1.1
+    #[serde(default)]
+    authors: Vec<JsonFeedAuthor>,
+    /// JSON Feed 1.0, deprecated in 1.1
+    author: Option<JsonFeedAuthor>,
+    #[serde(default)]
+    tags: Vec<String>,
+    image: Option<String>,
+    #[serde(default)]
+    attachments: Vec<JsonFeedAttachment>,
+}
+
+#[derive(Deserialize)]
+struct JsonFeedAttachment {
+    url: String,
+    mime_type: Option<String>,
+    size_in_bytes: Option<i64>,
+    duration_in_seconds: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct JsonFeedAuthor {
+    name: Option<String>,
+}
+
+fn parse_json_feed(
+    bytes: &[u8],
+    bases: &XmlBases,
+) -> anyhow::Result<(ParsedFeed, Vec<NewEntry>, usize)> {
+    let parsed: JsonFeed = serde_json::from_slice(strip_bom(bytes))?;
+
+    if !parsed.version.contains("jsonfeed.org/version/") {
+        anyhow::bail!("unknown json feed version: {}", parsed.version);
+    }
+
+    let mut diagnostics = Vec::new();
+
+    let (entries, skipped) =
+        parsed
+            .items
+            .iter()
+            .fold((Vec::new(), 0usize), |(mut entries, mut skipped), item| {
+                let url = match item.url.as_ref().or(item.external_url.as_ref()) {
+                    Some(url) => url.to_owned(),
+                    None => {
+                        warn!("no url found for item {:?}, skipping...", item.id);
+                        skipped += 1;
+                        return (entries, skipped);
+                    }
+                };
+
+                let (content, content_type) =
+                    match (item.content_html.as_deref(), item.content_text.as_deref()) {
+                        (Some(html), _) if !html.trim().is_empty() => {
+                            (Some(html.to_owned()), Some("html".to_string()))
+                        }
+                        (_, Some(text)) if !text.trim().is_empty() => {
+                            (Some(text.to_owned()), Some("text".to_string()))
+                        }
+                        _ => (None, None),
+                    };
+                let summary = non_empty(item.summary.as_deref());
+
+                entries.push(resolve_entry(
+                    bases.feed(),
+                    with_fallback_title(NewEntry {
+                        title: item.title.to_owned().unwrap_or_default(),
+                        url,
+                        comments_url: None,
+                        published_at: parse_first(item.date_published.as_deref(), &mut diagnostics),
+                        entry_updated_at: parse_first(
+                            item.date_modified.as_deref(),
+                            &mut diagnostics,
+                        ),
+                        summary_type: summary.as_ref().map(|_| "text".to_string()),
+                        summary,
+                        content,
+                        content_type,
+                        authors: unique(
+                            item.authors
+                                .iter()
+                                .chain(&item.author)
+                                .filter_map(|author| author.name.as_deref()),
+                        ),
+                        categories: unique(item.tags.iter().map(String::as_str)),
+                        enclosures: item
+                            .attachments
+                            .iter()
+                            .filter(|attachment| !attachment.url.trim().is_empty())
+                            .map(|attachment| Enclosure {
+                                url: attachment.url.trim().to_owned(),
+                                mime_type: non_empty(attachment.mime_type.as_deref()),
+                                length: attachment.size_in_bytes.filter(|size| *size > 0),
+                                duration_seconds: attachment
+                                    .duration_in_seconds
+                                    .map(|duration| duration as i64),
+                                image_url: non_empty(item.image.as_deref()),
+                            })
+                            .collect(),
+                        guid: item.id.as_ref().and_then(json_id),
+                    }),
+                ));
+
+                (entries, skipped)
+            });
+
+    Ok((
+        ParsedFeed {
+            title: parsed.title,
+            format: FeedFormat::Json,
+            site_url: non_empty(parsed.home_page_url.as_deref())
+                .map(|url| resolve(bases.feed(), &url)),
+            image_url: non_empty(parsed.favicon.as_deref().or(parsed.icon.as_deref()))
+                .map(|url| resolve(bases.feed(), &url)),
+            update_interval: None,
+            diagnostics,
+        },
+        entries,
+        skipped,
+    ))
+}
+
+/// The entry's page: the HTML `alternate` link, any other `alternate` link, or
+/// failing that a link that isn't metadata (`self`, `enclosure`, `replies`, ...).
+/// Some feeds have no links at all but use the page URL as the entry id.
+fn atom_entry_link(entry: &atom_syndication::Entry) -> Option<String> {
+    let alternates = || entry.links.iter().filter(|link| link.rel == "alternate");
+
+    alternates()
+        .find(|link| {
+            link.mime_type
+                .as_deref()
+                .is_none_or(|mime_type| mime_type.contains("html"))
+        })
+        .or_else(|| alternates().next())
+        .or_else(|| {
+            entry
+                .links
+                .iter()
+                .find(|link| !NON_PAGE_LINK_RELS.contains(&link.rel.as_str()))
+        })
+        .map(|link| link.href.to_owned())
+        .or_else(|| {
+            Url::parse(&entry.id)
+                .ok()
+                .filter(|id| matches!(id.scheme(), "http" | "https"))
+                .map(|_| entry.id.to_owned())
+        })
+}
+
+const NON_PAGE_LINK_RELS: &[&str] = &[
+    "self",
+    "enclosure",
+    "replies",
+    "edit",
+    "edit-media",
+    "license",
+    "hub",
+];
+
+/// Resolves the URLs of an entry against the base URL in effect for it and
+/// sanitizes its HTML bodies.
+fn resolve_entry(base: &Url, mut entry: NewEntry) -> NewEntry {
+    entry.url = resolve(base, &entry.url);
+    entry.comments_url = entry.comments_url.map(|url| resolve(base, &url));
+    for enclosure in &mut entry.enclosures {
+        enclosure.url = resolve(base, &enclosure.url);
+        enclosure.image_url = enclosure.image_url.take().map(|url| resolve(base, &url));
+    }
+    sanitize_entry(base, entry)
+}
+
+/// RSS `author` is meant to be an email address, optionally followed by a
+/// name in parentheses (`jane@example.com (Jane Doe)`). Prefer the name.
+fn rss_author(author: &str) -> &str {
+    author
+        .trim()
+        .strip_suffix(')')
+        .and_then(|rest| rest.split_once('('))
+        .map(|(_, name)| name.trim())
+        .filter(|name| !name.is_empty())
+        .unwrap_or(author)
+}
+
+/// Trimmed, non-empty values in order of first appearance, without
+/// case-insensitive duplicates.
+pub fn unique<'a>(values: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    values
+        .into_iter()
+        .map(str::trim)
+        .filter(|value| !value.is_empty() && seen.insert(value.to_lowercase()))
+        .map(str::to_owned)
+        .collect()
+}
+
+fn json_id(id: &serde_json::Value) -> Option<String> {
+    match id {
+        serde_json::Value::String(id) => non_empty(Some(id)),
+        serde_json::Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| value.to_owned())
+}
+
+fn text_type(text_type: atom_syndication::TextType) -> &'static str {
+    match text_type {
+        atom_syndication::TextType::Text => "text",
+        atom_syndication::TextType::Html => "html",
+        atom_syndication::TextType::Xhtml => "xhtml",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    const JSON_FEED: &[u8] = include_bytes!("fixtures/json_feed.json");
+    const RSS_DATES: &[u8] = include_bytes!("fixtures/rss_dates.xml");
+    const ATOM_DATES: &[u8] = include_bytes!("fixtures/atom_dates.xml");
+    const RSS_AUTHORS: &[u8] = include_bytes!("fixtures/rss_authors.xml");
+    const ATOM_AUTHORS: &[u8] = include_bytes!("fixtures/atom_authors.xml");
+    const ATOM_XML_BASE: &[u8] = include_bytes!("fixtures/atom_xml_base.xml");
+    const RSS_RELATIVE: &[u8] = include_bytes!("fixtures/rss_relative.xml");
+    const ATOM_LINKS: &[u8] = include_bytes!("fixtures/atom_links.xml");
+    const RSS_UNTITLED: &[u8] = include_bytes!("fixtures/rss_untitled.xml");
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn parses_json_feed() {
+        let (feed, entries) =
+            parse_feed(JSON_FEED, &url("https://json.example.com/feed.json")).unwrap();
+
+        assert_eq!(feed.title, "JSON Feed Blog");
+        assert_eq!(feed.site_url.as_deref(), Some("https://json.example.com/"));
+        assert_eq!(
+            feed.image_url.as_deref(),
+            Some("https://json.example.com/favicon-64.png")
+        );
+        assert_eq!(entries.len(), 2);
+
+        let first = &entries[0];
+        assert_eq!(first.title, "Second post");
+        assert_eq!(first.url, "https://json.example.com/posts/second");
+        assert_eq!(first.guid.as_deref(), Some("2"));
+        assert_eq!(first.authors, vec!["Jane Doe"]);
+        assert_eq!(first.categories, vec!["rust", "feeds"]);
+        assert_eq!(first.content.as_deref(), Some("<p>Hello <b>world</b></p>"));
+        assert_eq!(first.content_type.as_deref(), Some("html"));
+        assert_eq!(first.summary.as_deref(), Some("A second post"));
+        assert_eq!(
+            first.published_at,
+            Some(Utc.with_ymd_and_hms(2026, 2, 2, 8, 0, 0).unwrap())
+        );
+        assert_eq!(
+            first.entry_updated_at,
+            Some(Utc.with_ymd_and_hms(2026, 2, 3, 10, 0, 0).unwrap())
+        );
+
+        let second = &entries[1];
+        assert_eq!(second.url, "https://elsewhere.example.com/article");
+        assert_eq!(second.guid.as_deref(), Some("1"));
+        assert_eq!(second.authors, vec!["Old Style"]);
+        assert_eq!(
+            second.enclosures,
+            vec![Enclosure {
+                url: "https://json.example.com/audio.m4a".to_string(),
+                mime_type: Some("audio/x-m4a".to_string()),
+                length: Some(89970236),
+                duration_seconds: Some(6629),
+                image_url: Some("https://json.example.com/cover.png".to_string()),
+            }]
+        );
+        assert!(first.enclosures.is_empty());
+        assert_eq!(second.content.as_deref(), Some("Plain text body"));
+        assert_eq!(second.content_type.as_deref(), Some("text"));
+        assert!(second.summary.is_none());
+    }
+
+    #[test]
+    fn detects_json_feed_after_bom_and_whitespace() {
+        let mut bytes = b"\xEF\xBB\xBF\n  ".to_vec();
+        bytes.extend_from_slice(JSON_FEED);
+
+        let (feed, _) = parse_feed(&bytes, &url("https://json.example.com/feed.json")).unwrap();
+        assert_eq!(feed.title, "JSON Feed Blog");
+    }
+
+    #[test]
+    fn reports_feed_format() {
+        let format = |bytes| {
+            parse_feed(bytes, &url("https://example.com/feed"))
+                .unwrap()
+                .0
+                .format
+        };
+
+        assert_eq!(format(JSON_FEED), FeedFormat::Json);
+        assert_eq!(format(RSS_AUTHORS), FeedFormat::Rss);
+        assert_eq!(format(ATOM_AUTHORS), FeedFormat::Atom);
+    }
+
+    #[test]
+    fn rejects_json_that_is_not_a_feed() {
+        let result = parse_feed(br#"{"title": "not a feed"}"#, &url("https://example.com"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parses_sloppy_rss_dates_without_panicking() {
+        let (feed, entries) = parse_feed(RSS_DATES, &url("https://dates.example.com/rss")).unwrap();
+
+        let dates: Vec<_> = entries.iter().map(|entry| entry.published_at).collect();
+        assert_eq!(
+            dates,
+            vec![
+                Some(Utc.with_ymd_and_hms(2026, 1, 5, 14, 30, 0).unwrap()),
+                Some(Utc.with_ymd_and_hms(2026, 1, 6, 10, 0, 0).unwrap()),
+                Some(Utc.with_ymd_and_hms(2026, 1, 7, 11, 0, 0).unwrap()),
+                Some(Utc.with_ymd_and_hms(2026, 1, 8, 0, 0, 0).unwrap()),
+                None,
+            ]
+        );
+        assert_eq!(
+            feed.diagnostics,
+            vec![
+                "unparseable date \"sometime last week\"",
+                "unparseable date \"32/13/2026\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_sloppy_atom_dates() {
+        let (feed, entries) =
+            parse_feed(ATOM_DATES, &url("https://dates.example.com/atom")).unwrap();
+
+        let dates: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.published_at, entry.entry_updated_at))
+            .collect();
+        assert_eq!(
+            dates,
+            vec![
+                (
+                    Some(Utc.with_ymd_and_hms(2026, 1, 5, 14, 30, 0).unwrap()),
+                    Some(Utc.with_ymd_and_hms(2026, 1, 6, 10, 0, 0).unwrap()),
+                ),
+                (
+                    None,
+                    Some(Utc.with_ymd_and_hms(2026, 1, 7, 11, 0, 0).unwrap()),
+                ),
+                (None, None),
+            ]
+        );
+        assert_eq!(feed.diagnostics, vec!["unparseable date \"32/13/2026\""]);
+    }
+
+    #[test]
+    fn parses_rss_authors_and_categories() {
+        let (_, entries) = parse_feed(RSS_AUTHORS, &url("https://people.example.com/rss")).unwrap();
+
+        assert_eq!(entries[0].authors, vec!["Jane Doe", "John Roe"]);
+        assert_eq!(entries[0].categories, vec!["Politics", "Elections"]);
+        assert_eq!(entries[1].authors, vec!["editor@people.example.com"]);
+        assert!(entries[1].categories.is_empty());
+    }
+
+    #[test]
+    fn parses_atom_authors_and_categories() {
+        let (_, entries) =
+            parse_feed(ATOM_AUTHORS, &url("https://people.example.com/atom")).unwrap();
+
+        assert_eq!(entries[0].authors, vec!["Jane Doe"]);
+        assert_eq!(entries[0].categories, vec!["Science", "space"]);
+        // inherited from the feed
+        assert_eq!(entries[1].authors, vec!["The Editors"]);
+    }
+
+    #[test]
+    fn resolves_atom_xml_base() {
+        let (feed, entries) =
+            parse_feed(ATOM_XML_BASE, &url("https://base.example.com/feeds/atom")).unwrap();
+
+        assert_eq!(
+            feed.site_url.as_deref(),
+            Some("https://base.example.com/blog/")
+        );
+        assert_eq!(
+            entries[0].url,
+            "https://base.example.com/blog/2026/01/first"
+        );
+        assert_eq!(entries[1].url, "https://base.example.com/second");
+        assert_eq!(
+            entries[1].enclosures[0].url,
+            "https://cdn.example.net/audio/second.mp3"
+        );
+    }
+
+    #[test]
+    fn resolves_rss_relative_links() {
+        let (feed, entries) = parse_feed(
+            RSS_RELATIVE,
+            &url("https://relative.example.com/blog/rss.xml"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            feed.site_url.as_deref(),
+            Some("https://relative.example.com/blog/")
+        );
+        assert_eq!(entries[0].url, "https://relative.example.com/blog/posts/1");
+        assert_eq!(
+            entries[0].comments_url.as_deref(),
+            Some("https://relative.example.com/blog/posts/1#comments")
+        );
+        assert_eq!(entries[1].url, "https://relative.example.com/posts/2");
+    }
+
+    #[test]
+    fn picks_atom_alternate_links() {
+        let (feed, entries) =
+            parse_feed(ATOM_LINKS, &url("https://links.example.com/atom")).unwrap();
+
+        assert_eq!(
+            feed.image_url.as_deref(),
+            Some("https://links.example.com/icon.png")
+        );
+
+        let urls: Vec<_> = entries.iter().map(|entry| entry.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://links.example.com/1",
+                "https://links.example.com/2",
+                "https://links.example.com/3",
+                "https://links.example.com/4",
+            ]
+        );
+        assert_eq!(entries[3].title, "Status update: look at this");
+    }
+
+    #[test]
+    fn reads_update_interval() {
+        let (feed, _) = parse_feed(RSS_DATES, &url("https://dates.example.com/rss")).unwrap();
+        // the longer of <ttl> and sy:updatePeriod
+        assert_eq!(feed.update_interval, Some(Duration::from_secs(2 * 60 * 60)));
+
+        let (feed, _) = parse_feed(ATOM_LINKS, &url("https://links.example.com/atom")).unwrap();
+        assert_eq!(
+            feed.update_interval,
+            Some(Duration::from_secs(24 * 60 * 60))
+        );
+
+        let (feed, _) = parse_feed(JSON_FEED, &url("https://json.example.com/feed.json")).unwrap();
+        assert_eq!(feed.update_interval, None);
+    }
+
+    #[test]
+    fn keeps_untitled_rss_items() {
+        let (_, entries) = parse_feed(RSS_UNTITLED, &url("https://micro.example.com/rss")).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "Trying out the new feed reader.");
+        assert_eq!(entries[1].title, "Post from 2026-01-06 10:00 UTC");
+    }
+}
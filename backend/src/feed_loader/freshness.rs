@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{self, HeaderMap};
+
+/// How long a response stays fresh by its `Cache-Control: max-age`, or else
+/// its `Expires` relative to its `Date` (RFC 9111 section 4.2.1). `no-cache`
+/// and `no-store` make it stale right away.
+pub fn freshness_lifetime(headers: &HeaderMap) -> Option<Duration> {
+    let directives: Vec<String> = headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|directive| directive.trim().to_ascii_lowercase())
+        .collect();
+
+    if directives
+        .iter()
+        .any(|directive| directive == "no-cache" || directive == "no-store")
+    {
+        return Some(Duration::ZERO);
+    }
+
+    let max_age = directives
+        .iter()
+        .find_map(|directive| directive.strip_prefix("max-age="))
+        .and_then(|seconds| seconds.trim_matches('"').parse::<u64>().ok());
+    if let Some(max_age) = max_age {
+        return Some(Duration::from_secs(max_age));
+    }
+
+    // an invalid or past date means already expired
+    let Some(expires) = http_date(headers.get(header::EXPIRES)?) else {
+        return Some(Duration::ZERO);
+    };
+    let date = headers
+        .get(header::DATE)
+        .and_then(http_date)
+        .unwrap_or_else(Utc::now);
+    Some((expires - date).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// `Retry-After`, either in seconds or as a date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?;
+    if let Ok(seconds) = value.to_str().ok()?.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = http_date(value)?;
+    Some((date - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
+
+fn http_date(value: &header::HeaderValue) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.to_str().ok()?.trim())
+        .ok()
+        .map(|date| date.to_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn reads_max_age() {
+        assert_eq!(freshness_lifetime(&HeaderMap::new()), None);
+        assert_eq!(
+            freshness_lifetime(&headers(&[(header::CACHE_CONTROL, "public, max-age=600")])),
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(
+            freshness_lifetime(&headers(&[(
+                header::CACHE_CONTROL,
+                "no-cache, max-age=600"
+            )])),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            freshness_lifetime(&headers(&[(header::CACHE_CONTROL, "max-age=soon")])),
+            None
+        );
+    }
+
+    #[test]
+    fn reads_retry_after() {
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+        assert_eq!(
+            retry_after(&headers(&[(header::RETRY_AFTER, "120")])),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            retry_after(&headers(&[(
+                header::RETRY_AFTER,
+                "Thu, 01 Jan 1970 00:00:00 GMT"
+            )])),
+            Some(Duration::ZERO)
+        );
+        let in_an_hour = (Utc::now() + chrono::Duration::hours(1)).to_rfc2822();
+        let wait = retry_after(&headers(&[(header::RETRY_AFTER, &in_an_hour)])).unwrap();
+        assert!(wait > Duration::from_secs(59 * 60) && wait <= Duration::from_secs(60 * 60));
+        assert_eq!(
+            retry_after(&headers(&[(header::RETRY_AFTER, "later")])),
+            None
+        );
+    }
+
+    #[test]
+    fn reads_expires_relative_to_date() {
+        assert_eq!(
+            freshness_lifetime(&headers(&[
+                (header::DATE, "Wed, 21 Oct 2015 07:28:00 GMT"),
+                (header::EXPIRES, "Wed, 21 Oct 2015 09:28:00 GMT"),
+            ])),
+            Some(Duration::from_secs(2 * 60 * 60))
+        );
+        assert_eq!(
+            freshness_lifetime(&headers(&[
+                (header::CACHE_CONTROL, "max-age=60"),
+                (header::DATE, "Wed, 21 Oct 2015 07:28:00 GMT"),
+                (header::EXPIRES, "Wed, 21 Oct 2015 09:28:00 GMT"),
+            ])),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            freshness_lifetime(&headers(&[(header::EXPIRES, "0")])),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            freshness_lifetime(&headers(&[(
+                header::EXPIRES,
+                "Thu, 01 Jan 1970 00:00:00 GMT"
+            )])),
+            Some(Duration::ZERO)
+        );
+    }
+}
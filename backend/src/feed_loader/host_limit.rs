@@ -0,0 +1,171 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use once_cell::sync::{Lazy, OnceCell};
+use reqwest::{RequestBuilder, Response};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::Instant,
+};
+use url::Url;
+
+/// How hard any one site is hit, across every feed, icon and robots.txt
+/// request.
+#[derive(Debug, Clone, Copy)]
+pub struct HostLimits {
+    /// Requests to the same host in flight at once.
+    pub max_concurrent: usize,
+    /// Time between the starts of two requests to the same host.
+    pub min_delay: Duration,
+}
+
+impl Default for HostLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 2,
+            min_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+static HOST_LIMITS: OnceCell<HostLimits> = OnceCell::new();
+
+/// Sets the limits every host is held to. It has to be called before the
+/// first request.
+pub fn set_host_limits(limits: HostLimits) {
+    if HOST_LIMITS.set(limits).is_err() {
+        tracing::warn!("host limits already set");
+    }
+}
+
+fn host_limits() -> HostLimits {
+    HOST_LIMITS.get().copied().unwrap_or_default()
+}
+
+/// Hosts with requests in flight or recently started.
+static HOSTS: Lazy<Mutex<HashMap<String, Arc<HostState>>>> = Lazy::new(Mutex::default);
+
+struct HostState {
+    requests: Arc<Semaphore>,
+    /// When the next request may start; each request pushes it back.
+    next_start: Mutex<Instant>,
+}
+
+/// Sends `request` to `url` once the host's limits allow it. The response
+/// holds on to the host's slot until it's dropped, so reading the body counts
+/// as part of the request.
+pub(super) async fn send(url: &Url, request: RequestBuilder) -> reqwest::Result<Response> {
+    let permit = acquire(url).await;
+    let mut response = request.send().await?;
+    if let Some(permit) = permit {
+        response.extensions_mut().insert(Arc::new(permit));
+    }
+    Ok(response)
+}
+
+async fn acquire(url: &Url) -> Option<OwnedSemaphorePermit> {
+    let host = url.host_str()?.to_owned();
+    let limits = host_limits();
+    let max_concurrent = limits.max_concurrent.max(1);
+
+    let state = {
+        let mut hosts = HOSTS.lock().expect("host limits lock poisoned");
+        let now = Instant::now();
+        hosts.retain(|_, state| {
+            Arc::strong_count(state) > 1
+                || state.requests.available_permits() < max_concurrent
+                || *state.next_start.lock().expect("host limits lock poisoned") > now
+        });
+        hosts
+            .entry(host.clone())
+            .or_insert_with(|| {
+                Arc::new(HostState {
+                    requests: Arc::new(Semaphore::new(max_concurrent)),
+                    next_start: Mutex::new(now),
+                })
+            })
+            .clone()
+    };
+
+    let permit = state
+        .requests
+        .clone()
+        .acquire_owned()
+        .await
+        .expect("host semaphore is never closed");
+
+    let start_at = {
+        let mut next_start = state.next_start.lock().expect("host limits lock poisoned");
+        let start_at = (*next_start).max(Instant::now());
+        *next_start = start_at + limits.min_delay;
+        start_at
+    };
+    if start_at > Instant::now() {
+        tracing::trace!(host, wait = ?(start_at - Instant::now()), "waiting for host");
+        tokio::time::sleep_until(start_at).await;
+    }
+
+    Some(permit)
+}
+
+/// Reorders `items` so that consecutive ones are on different hosts where
+/// possible, keeping the order within each host. Concurrent syncs then spread
+/// over sites instead of queueing on the busiest one.
+pub fn interleave_hosts<T>(items: Vec<T>, url: impl Fn(&T) -> &str) -> Vec<T> {
+    let mut order: Vec<String> = Vec::new();
+    let mut by_host: HashMap<String, VecDeque<T>> = HashMap::new();
+    for item in items {
+        let host = Url::parse(url(&item))
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_owned))
+            .unwrap_or_default();
+        if !by_host.contains_key(&host) {
+            order.push(host.clone());
+        }
+        by_host.entry(host).or_default().push_back(item);
+    }
+
+    let mut interleaved = Vec::new();
+    while !by_host.is_empty() {
+        for host in &order {
+            if let Some(queue) = by_host.get_mut(host) {
+                interleaved.extend(queue.pop_front());
+                if queue.is_empty() {
+                    by_host.remove(host);
+                }
+            }
+        }
+    }
+    interleaved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interleaves_by_host() {
+        let urls = vec![
+            "https://github.com/a/releases.atom",
+            "https://github.com/b/releases.atom",
+            "https://github.com/c/releases.atom",
+            "https://example.com/feed.xml",
+            "https://GitHub.com/d/releases.atom",
+            "https://blog.example.org/rss",
+        ];
+        assert_eq!(
+            interleave_hosts(urls, |url| url),
+            vec![
+                "https://github.com/a/releases.atom",
+                "https://example.com/feed.xml",
+                "https://blog.example.org/rss",
+                "https://github.com/b/releases.atom",
+                "https://github.com/c/releases.atom",
+                "https://GitHub.com/d/releases.atom",
+            ]
+        );
+    }
+}
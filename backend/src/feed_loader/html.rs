@@ -1,14 +1,41 @@
-use std::rc::Rc;
-
 use html5ever::{ParseOpts, parse_document, tendril::TendrilSink, tree_builder::TreeBuilderOpts};
-use markup5ever_rcdom::{Node, NodeData, RcDom};
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use url::Url;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HtmlError {
+    #[error("failed to read html: {0}")]
+    Read(#[from] std::io::Error),
+}
 
+/// What feed and icon discovery need from a page, collected from the whole
+/// document rather than assuming a well-formed `<html><head><body>`.
 pub struct Html {
-    head_children: Vec<Node>,
+    /// `href` of the first `<base>` element.
+    base_href: Option<String>,
+    /// `<link>` elements, wherever they appear.
+    links: Vec<Link>,
+    /// `href` and text of the `<a>` elements in the body.
+    body_links: Vec<(String, String)>,
+}
+
+struct Link {
+    rel: Option<String>,
+    href: Option<String>,
+    link_type: Option<String>,
+    sizes: Option<String>,
+}
+
+/// A `<link>` to one of the site's icons.
+pub struct IconLink {
+    pub href: String,
+    pub rel: String,
+    pub sizes: Option<String>,
+    pub link_type: Option<String>,
 }
 
 impl Html {
-    pub fn from_bytes(mut bytes: &[u8]) -> Self {
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, HtmlError> {
         let rc_dom = parse_document(
             RcDom::default(),
             ParseOpts {
@@ -20,113 +47,307 @@ impl Html {
             },
         )
         .from_utf8()
-        .read_from(&mut bytes)
-        // TODO
-        .unwrap();
+        .read_from(&mut bytes)?;
 
-        let head_children = {
-            let document_children = rc_dom.document.children.borrow();
+        let mut html = Self {
+            base_href: None,
+            links: Vec::new(),
+            body_links: Vec::new(),
+        };
 
-            let html_elem = document_children
-                .iter()
-                .find(|child| match &child.data {
-                    NodeData::Element { name, .. } => name.local.as_ref() == "html",
-                    _ => false,
-                })
-                // TODO
-                .unwrap();
+        // walked with an explicit stack, as hostile pages can nest deeply
+        // enough to overflow a recursive walk
+        let mut stack = vec![rc_dom.document.clone()];
+        while let Some(node) = stack.pop() {
+            if let NodeData::Element { name, attrs, .. } = &node.data {
+                let attrs = attrs.borrow();
+                let attr = |name: &str| {
+                    attrs
+                        .iter()
+                        .find(|attr| attr.name.local.as_ref() == name)
+                        .map(|attr| attr.value.to_string())
+                };
 
-            let html_children = html_elem.children.borrow();
+                match name.local.as_ref() {
+                    "base" if html.base_href.is_none() => html.base_href = attr("href"),
+                    "link" => html.links.push(Link {
+                        rel: attr("rel"),
+                        href: attr("href"),
+                        link_type: attr("type"),
+                        sizes: attr("sizes"),
+                    }),
+                    // an unclosed `<a>` can hold the rest of the page, so its
+                    // children are still walked
+                    "a" => {
+                        if let Some(href) = attr("href") {
+                            html.body_links.push((href, text_content(&node)));
+                        }
+                    }
+                    _ => {}
+                }
+            }
 
-            let head_elem = html_children
-                .iter()
-                .find(|child| match &child.data {
-                    NodeData::Element { name, .. } => name.local.as_ref() == "head",
-                    _ => false,
-                })
-                // TODO
-                .unwrap();
-
-            head_elem
-                .children
-                .take()
-                .into_iter()
-                .map(|rc| {
-                    Rc::try_unwrap(rc)
-                        // TODO
-                        .unwrap()
-                })
-                .collect()
-        };
+            // reversed so that elements are visited in document order
+            stack.extend(node.children.borrow().iter().rev().cloned());
+        }
 
-        return Self { head_children };
+        Ok(html)
     }
 
-    pub fn favicon_urls(&self) -> Vec<String> {
-        self.head_children
-            .iter()
-            .filter_map(|child| {
-                let NodeData::Element { name, attrs, .. } = &child.data else {
-                    return None;
-                };
+    /// The URL relative links in the page resolve against: `<base href>` if
+    /// present, otherwise the URL the page was fetched from.
+    pub fn base_url(&self, page_url: &Url) -> Url {
+        self.base_href
+            .as_deref()
+            .and_then(|href| page_url.join(href.trim()).ok())
+            .unwrap_or_else(|| page_url.to_owned())
+    }
 
-                if name.local.as_ref() != "link" {
+    pub fn icon_links(&self) -> Vec<IconLink> {
+        self.links
+            .iter()
+            .filter_map(|link| {
+                let rel = link.rel.as_deref()?;
+                let is_icon = rel.split_ascii_whitespace().any(|rel| {
+                    ICON_RELS
+                        .iter()
+                        .any(|icon_rel| icon_rel.eq_ignore_ascii_case(rel))
+                });
+                if !is_icon {
                     return None;
                 }
 
-                let borrowed_attrs = attrs.borrow();
+                Some(IconLink {
+                    href: link.href.clone()?,
+                    rel: rel.to_owned(),
+                    sizes: link.sizes.clone(),
+                    link_type: link.link_type.clone(),
+                })
+            })
+            .collect()
+    }
 
-                let rel_value = borrowed_attrs
-                    .iter()
-                    .find(|attr| attr.name.local.as_ref() == "rel")
-                    .map(|attr| attr.value.as_ref())?;
+    /// The web app manifest, which may list more icons.
+    pub fn manifest_url(&self) -> Option<String> {
+        self.links
+            .iter()
+            .find(|link| {
+                link.rel.as_deref().is_some_and(|rel| {
+                    rel.split_ascii_whitespace()
+                        .any(|rel| rel.eq_ignore_ascii_case("manifest"))
+                })
+            })
+            .and_then(|link| link.href.clone())
+    }
 
-                if !ICON_RELS.contains(&rel_value) {
-                    return None;
-                }
+    /// Feeds advertised with `<link rel="alternate">` and a feed type.
+    pub fn feed_urls(&self) -> Vec<String> {
+        self.links
+            .iter()
+            .filter(|link| {
+                let is_alternate = link.rel.as_deref().is_some_and(|rel| {
+                    rel.split_ascii_whitespace().any(|rel| {
+                        rel.eq_ignore_ascii_case("alternate") || rel.eq_ignore_ascii_case("feed")
+                    })
+                });
+                let is_feed_type = link.link_type.as_deref().is_some_and(|link_type| {
+                    let link_type = link_type.split(';').next().unwrap_or_default().trim();
+                    FEED_TYPES
+                        .iter()
+                        .any(|feed_type| feed_type.eq_ignore_ascii_case(link_type))
+                });
 
-                borrowed_attrs
-                    .iter()
-                    .find(|attr| attr.name.local.as_ref() == "href")
-                    .map(|attr| attr.value.to_string())
+                is_alternate && is_feed_type
             })
+            .filter_map(|link| link.href.clone())
             .collect()
     }
 
-    pub fn feed_urls(&self) -> Vec<String> {
-        self.head_children
+    /// Links in the body that look like they lead to a feed, e.g. an "RSS"
+    /// link in the footer. Unlike `feed_urls` these are guesses.
+    pub fn body_feed_urls(&self) -> Vec<String> {
+        self.body_links
             .iter()
-            .filter_map(|child| {
-                let NodeData::Element { name, attrs, .. } = &child.data else {
-                    return None;
-                };
+            .filter(|(href, text)| looks_like_feed_link(href, text))
+            .map(|(href, _)| href.to_owned())
+            .collect()
+    }
+}
 
-                if name.local.as_ref() != "link" {
-                    return None;
-                }
+fn text_content(node: &Handle) -> String {
+    let mut text = String::new();
+    let mut stack = vec![node.clone()];
+    while let Some(node) = stack.pop() {
+        if let NodeData::Text { contents } = &node.data {
+            text.push_str(&contents.borrow());
+        }
+        stack.extend(node.children.borrow().iter().rev().cloned());
+    }
+    text
+}
 
-                let borrowed_attrs = attrs.borrow();
+fn looks_like_feed_link(href: &str, text: &str) -> bool {
+    let path = href
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .trim_end_matches('/')
+        .to_ascii_lowercase();
+    if FEED_PATH_SUFFIXES
+        .iter()
+        .any(|suffix| path.ends_with(suffix))
+    {
+        return true;
+    }
 
-                let is_feed = borrowed_attrs.iter().any(|attr| {
-                    let attr_name = attr.name.local.as_ref();
-                    let attr_value = attr.value.as_ref();
-                    let contains_feed_keyword =
-                        attr_value.contains("rss") || attr_value.contains("atom");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    FEED_LINK_TEXTS.contains(&text.to_lowercase().as_str())
+}
 
-                    (attr_name == "href" || attr_name == "type") && contains_feed_keyword
-                });
+const FEED_TYPES: &[&str] = &[
+    "application/rss+xml",
+    "application/atom+xml",
+    "application/feed+json",
+    "application/rdf+xml",
+];
 
-                if !is_feed {
-                    return None;
-                }
+const FEED_PATH_SUFFIXES: &[&str] = &[
+    "/feed",
+    "/rss",
+    "/atom",
+    ".rss",
+    ".atom",
+    ".rdf",
+    "rss.xml",
+    "atom.xml",
+    "feed.xml",
+    "index.xml",
+    "feed.json",
+];
 
-                borrowed_attrs
-                    .iter()
-                    .find(|attr| attr.name.local.as_ref() == "href")
-                    .map(|attr| attr.value.to_string())
-            })
-            .collect()
+const FEED_LINK_TEXTS: &[&str] = &[
+    "rss",
+    "atom",
+    "feed",
+    "rss feed",
+    "atom feed",
+    "json feed",
+    "subscribe via rss",
+];
+
+/// `shortcut icon` is matched by `icon`, as rels are tokens.
+const ICON_RELS: &[&str] = &["icon", "apple-touch-icon", "apple-touch-icon-precomposed"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DISCOVERY_PAGE: &[u8] = include_bytes!("fixtures/discovery_page.html");
+    const MALFORMED_PAGE: &[u8] = include_bytes!("fixtures/malformed_page.html");
+    const FRAMESET_PAGE: &[u8] = include_bytes!("fixtures/frameset_page.html");
+    const XHTML_PAGE: &[u8] = include_bytes!("fixtures/xhtml_page.xhtml");
+
+    fn hrefs(links: Vec<IconLink>) -> Vec<String> {
+        links.into_iter().map(|link| link.href).collect()
     }
-}
 
-const ICON_RELS: &[&str] = &["icon", "shortcut icon", "apple-touch-icon"];
+    #[test]
+    fn finds_alternate_feed_links_only() {
+        let html = Html::from_bytes(DISCOVERY_PAGE).unwrap();
+
+        assert_eq!(html.feed_urls(), vec!["/feed.xml", "/feed.json"]);
+    }
+
+    #[test]
+    fn finds_feed_links_in_body() {
+        let html = Html::from_bytes(DISCOVERY_PAGE).unwrap();
+
+        assert_eq!(
+            html.body_feed_urls(),
+            vec![
+                "/blog/rss/",
+                "https://example.com/podcast.rss",
+                "/subscribe?via=reader"
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_malformed_pages() {
+        let html = Html::from_bytes(MALFORMED_PAGE).unwrap();
+
+        assert_eq!(html.feed_urls(), vec!["/atom.xml", "/late.rss"]);
+        assert_eq!(hrefs(html.icon_links()), vec!["/icon.png"]);
+        assert_eq!(html.body_feed_urls(), vec!["/feed", "/after-the-end"]);
+        assert_eq!(
+            html.base_url(&Url::parse("https://example.com/page").unwrap())
+                .as_str(),
+            "https://cdn.example.com/"
+        );
+    }
+
+    #[test]
+    fn reads_framesets() {
+        let html = Html::from_bytes(FRAMESET_PAGE).unwrap();
+
+        assert_eq!(html.feed_urls(), vec!["/news.rss"]);
+        assert_eq!(hrefs(html.icon_links()), vec!["/favicon.ico"]);
+    }
+
+    #[test]
+    fn reads_xhtml() {
+        let html = Html::from_bytes(XHTML_PAGE).unwrap();
+        let base = html.base_url(&Url::parse("https://xhtml.example.com/").unwrap());
+
+        assert_eq!(base.as_str(), "https://xhtml.example.com/blog/");
+        assert_eq!(html.feed_urls(), vec!["atom.xml"]);
+        assert_eq!(hrefs(html.icon_links()), vec!["icon.png"]);
+        assert_eq!(html.body_feed_urls(), vec!["index.xml"]);
+    }
+
+    #[test]
+    fn reads_empty_and_binary_documents() {
+        for bytes in [&b""[..], b"\x00\xff\xfe garbage \x89PNG", b"just text"] {
+            let html = Html::from_bytes(bytes).unwrap();
+            assert!(html.feed_urls().is_empty());
+            assert!(html.body_feed_urls().is_empty());
+        }
+    }
+
+    #[test]
+    fn reads_deeply_nested_pages() {
+        let depth = 100_000;
+        let page = format!(
+            "{}<a href=\"/feed.xml\">RSS</a>{}",
+            "<span>".repeat(depth),
+            "</span>".repeat(depth)
+        );
+
+        let html = Html::from_bytes(page.as_bytes()).unwrap();
+        assert_eq!(html.body_feed_urls(), vec!["/feed.xml"]);
+    }
+
+    #[test]
+    fn finds_icon_links_and_manifest() {
+        let html = Html::from_bytes(
+            br#"<head>
+                <link rel="Shortcut Icon" href="/favicon.ico">
+                <link rel="icon" type="image/png" sizes="32x32" href="/32.png">
+                <link rel="apple-touch-icon-precomposed" href="/touch.png">
+                <link rel="mask-icon" href="/mask.svg">
+                <link rel="manifest" href="/site.webmanifest">
+            </head>"#,
+        )
+        .unwrap();
+
+        let links = html.icon_links();
+        assert_eq!(
+            links.iter().map(|l| l.href.as_str()).collect::<Vec<_>>(),
+            vec!["/favicon.ico", "/32.png", "/touch.png"]
+        );
+        assert_eq!(links[1].sizes.as_deref(), Some("32x32"));
+        assert_eq!(links[1].link_type.as_deref(), Some("image/png"));
+        assert_eq!(html.manifest_url().as_deref(), Some("/site.webmanifest"));
+    }
+}
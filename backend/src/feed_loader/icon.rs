@@ -0,0 +1,325 @@
+use std::io::Cursor;
+
+use image::{ImageFormat, ImageReader, Limits, imageops::FilterType};
+use serde::Deserialize;
+use url::Url;
+
+/// Icons are stored as PNGs no larger than this on either side.
+pub const ICON_SIZE: u32 = 64;
+
+/// Icons (and manifests) larger than this aren't downloaded.
+pub const MAX_ICON_BYTES: usize = 1024 * 1024;
+
+/// Guards against images that are small on the wire but huge once decoded.
+const MAX_DECODED_DIMENSION: u32 = 4096;
+const MAX_DECODED_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Where an icon candidate was found. Earlier sources are preferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IconSource {
+    /// RSS `<image>`, Atom `<icon>`/`<logo>` or JSON Feed `favicon`/`icon`.
+    Feed,
+    /// `<link rel="icon">` and friends, or a web app manifest.
+    Site,
+    /// `/favicon.ico`, requested whether or not the site mentions it.
+    Fallback,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IconCandidate {
+    pub url: String,
+    pub source: IconSource,
+    /// The larger side, as declared by `sizes`.
+    pub size: Option<u32>,
+    pub mime_type: Option<String>,
+}
+
+impl IconCandidate {
+    pub fn new(url: String, source: IconSource) -> Self {
+        Self {
+            url,
+            source,
+            size: None,
+            mime_type: None,
+        }
+    }
+
+    /// A `<link>` icon. `rel` decides the default size, as Apple touch icons
+    /// without `sizes` are 180 pixels.
+    pub fn from_link(url: String, rel: &str, sizes: Option<&str>, mime_type: Option<&str>) -> Self {
+        let is_apple_touch = rel
+            .split_ascii_whitespace()
+            .any(|rel| rel.to_ascii_lowercase().starts_with("apple-touch-icon"));
+
+        Self {
+            url,
+            source: IconSource::Site,
+            size: sizes
+                .and_then(parse_sizes)
+                .or(is_apple_touch.then_some(180)),
+            mime_type: mime_type.map(|mime_type| mime_type.trim().to_ascii_lowercase()),
+        }
+    }
+
+    fn format_rank(&self) -> Option<u8> {
+        let mime_type = self.mime_type.as_deref().unwrap_or_default();
+        let path = self
+            .url
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let is = |mime_types: &[&str], extensions: &[&str]| {
+            mime_types.contains(&mime_type) || extensions.iter().any(|ext| path.ends_with(ext))
+        };
+
+        if is(&["image/svg+xml"], &[".svg", ".svgz"]) {
+            // can't be rendered to a PNG
+            None
+        } else if is(&["image/png"], &[".png"]) {
+            Some(0)
+        } else if is(&["image/x-icon", "image/vnd.microsoft.icon"], &[".ico"]) {
+            Some(1)
+        } else if mime_type.is_empty() {
+            Some(3)
+        } else {
+            Some(2)
+        }
+    }
+
+    /// Prefers the smallest icon at least `ICON_SIZE` big, then the largest
+    /// smaller one, then icons of unknown size.
+    fn size_rank(&self) -> (u8, u32) {
+        match self.size {
+            Some(size) if size >= ICON_SIZE => (0, size),
+            Some(size) => (1, u32::MAX - size),
+            None => (2, 0),
+        }
+    }
+}
+
+/// Orders candidates best first, dropping duplicates and formats that can't
+/// be decoded.
+pub fn rank_icons(candidates: Vec<IconCandidate>) -> Vec<IconCandidate> {
+    let mut ranked: Vec<(IconCandidate, u8)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let format_rank = candidate.format_rank()?;
+            Some((candidate, format_rank))
+        })
+        .collect();
+    ranked.sort_by_key(|(candidate, format_rank)| {
+        (candidate.source, candidate.size_rank(), *format_rank)
+    });
+
+    let mut unique: Vec<IconCandidate> = Vec::new();
+    for (candidate, _) in ranked {
+        if !unique.iter().any(|c| c.url == candidate.url) {
+            unique.push(candidate);
+        }
+    }
+    unique
+}
+
+/// The larger side of the largest size in a `sizes` attribute, e.g. 32 for
+/// `"16x16 32x32"`. `any` means scalable, which is as good as it gets.
+fn parse_sizes(sizes: &str) -> Option<u32> {
+    sizes
+        .split_ascii_whitespace()
+        .filter_map(|size| {
+            if size.eq_ignore_ascii_case("any") {
+                return Some(u32::MAX);
+            }
+            let (width, height) = size
+                .to_ascii_lowercase()
+                .split_once('x')
+                .map(|(w, h)| (w.parse::<u32>().ok(), h.parse::<u32>().ok()))?;
+            Some(width?.max(height?))
+        })
+        .max()
+}
+
+/// https://www.w3.org/TR/appmanifest/#icons-member
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    icons: Vec<ManifestIcon>,
+}
+
+#[derive(Deserialize)]
+struct ManifestIcon {
+    src: String,
+    sizes: Option<String>,
+    r#type: Option<String>,
+    purpose: Option<String>,
+}
+
+/// The icons of a web app manifest, with `src` resolved against the
+/// manifest's URL. Maskable-only icons are skipped, as they are padded to be
+/// cropped.
+pub fn manifest_icons(bytes: &[u8], manifest_url: &Url) -> Vec<IconCandidate> {
+    let Ok(manifest) = serde_json::from_slice::<Manifest>(bytes) else {
+        tracing::debug!(%manifest_url, "failed to parse web app manifest");
+        return Vec::new();
+    };
+
+    manifest
+        .icons
+        .into_iter()
+        .filter(|icon| {
+            icon.purpose.as_deref().is_none_or(|purpose| {
+                purpose
+                    .split_ascii_whitespace()
+                    .any(|purpose| purpose.eq_ignore_ascii_case("any"))
+            })
+        })
+        .filter_map(|icon| {
+            let url = manifest_url.join(icon.src.trim()).ok()?;
+            Some(IconCandidate {
+                url: url.to_string(),
+                source: IconSource::Site,
+                size: icon.sizes.as_deref().and_then(parse_sizes),
+                mime_type: icon.r#type.map(|t| t.trim().to_ascii_lowercase()),
+            })
+        })
+        .collect()
+}
+
+/// Decodes an icon in any supported format, ICO included, and re-encodes it
+/// as a PNG scaled down to fit `ICON_SIZE`.
+pub fn normalize_icon(bytes: &[u8]) -> Result<Vec<u8>, image::ImageError> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DECODED_DIMENSION);
+    limits.max_image_height = Some(MAX_DECODED_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODED_BYTES);
+
+    let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    reader.limits(limits);
+    let mut icon = reader.decode()?;
+
+    if icon.width() > ICON_SIZE || icon.height() > ICON_SIZE {
+        icon = icon.resize(ICON_SIZE, ICON_SIZE, FilterType::Lanczos3);
+    }
+
+    let mut png = Vec::new();
+    icon.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, RgbaImage};
+
+    use super::*;
+
+    fn link(url: &str, sizes: Option<&str>, mime_type: Option<&str>) -> IconCandidate {
+        IconCandidate::from_link(url.to_string(), "icon", sizes, mime_type)
+    }
+
+    fn urls(candidates: &[IconCandidate]) -> Vec<&str> {
+        candidates.iter().map(|c| c.url.as_str()).collect()
+    }
+
+    fn encode(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let image = DynamicImage::ImageRgba8(RgbaImage::new(width, height));
+        let mut bytes = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), format)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_sizes("16x16"), Some(16));
+        assert_eq!(parse_sizes("16x16 32X32 24x24"), Some(32));
+        assert_eq!(parse_sizes("120x60"), Some(120));
+        assert_eq!(parse_sizes("any"), Some(u32::MAX));
+        assert_eq!(parse_sizes("large"), None);
+    }
+
+    #[test]
+    fn ranks_by_source_size_and_format() {
+        let ranked = rank_icons(vec![
+            IconCandidate::new("https://e.com/favicon.ico".into(), IconSource::Fallback),
+            link("https://e.com/16.png", Some("16x16"), None),
+            link("https://e.com/any.svg", Some("any"), Some("image/svg+xml")),
+            link("https://e.com/32.ico", Some("32x32"), None),
+            link("https://e.com/32.png", Some("32x32"), Some("image/png")),
+            link("https://e.com/512.png", Some("512x512"), None),
+            link("https://e.com/96.png", Some("96x96"), None),
+            link("https://e.com/unsized", None, None),
+            IconCandidate::from_link(
+                "https://e.com/touch.png".into(),
+                "apple-touch-icon",
+                None,
+                None,
+            ),
+            IconCandidate::new("https://e.com/feed.png".into(), IconSource::Feed),
+            link("https://e.com/96.png", Some("96x96"), None),
+        ]);
+
+        assert_eq!(
+            urls(&ranked),
+            vec![
+                "https://e.com/feed.png",
+                "https://e.com/96.png",
+                "https://e.com/touch.png",
+                "https://e.com/512.png",
+                "https://e.com/32.png",
+                "https://e.com/32.ico",
+                "https://e.com/16.png",
+                "https://e.com/unsized",
+                "https://e.com/favicon.ico",
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_manifest_icons() {
+        let manifest = br#"{
+            "name": "Example",
+            "icons": [
+                { "src": "icons/192.png", "sizes": "192x192", "type": "image/png" },
+                { "src": "/icons/maskable.png", "sizes": "512x512", "purpose": "maskable" },
+                { "src": "https://cdn.example.com/48.png", "sizes": "48x48", "purpose": "any maskable" }
+            ]
+        }"#;
+        let manifest_url = Url::parse("https://example.com/app/manifest.json").unwrap();
+
+        let icons = manifest_icons(manifest, &manifest_url);
+        assert_eq!(
+            urls(&icons),
+            vec![
+                "https://example.com/app/icons/192.png",
+                "https://cdn.example.com/48.png"
+            ]
+        );
+        assert_eq!(icons[0].size, Some(192));
+        assert_eq!(icons[0].mime_type.as_deref(), Some("image/png"));
+
+        assert!(manifest_icons(b"not json", &manifest_url).is_empty());
+    }
+
+    #[test]
+    fn normalizes_to_small_png() {
+        let png = normalize_icon(&encode(256, 128, ImageFormat::Png)).unwrap();
+
+        let icon = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+        assert_eq!((icon.width(), icon.height()), (64, 32));
+    }
+
+    #[test]
+    fn normalizes_ico() {
+        let png = normalize_icon(&encode(32, 32, ImageFormat::Ico)).unwrap();
+
+        let icon = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+        assert_eq!((icon.width(), icon.height()), (32, 32));
+    }
+
+    #[test]
+    fn rejects_undecodable_icons() {
+        assert!(normalize_icon(b"<html>not an icon</html>").is_err());
+        assert!(normalize_icon(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>").is_err());
+    }
+}
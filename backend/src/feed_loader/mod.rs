@@ -1,24 +1,64 @@
-use once_cell::sync::Lazy;
-use reqwest::{Client, Response, StatusCode, redirect};
-use std::collections::HashMap;
-use std::time::Duration;
-use texting_robots::Robot;
+use once_cell::sync::{Lazy, OnceCell};
+use reqwest::{
+    Client, ClientBuilder, Proxy, Response, StatusCode,
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::{self, HeaderMap},
+    redirect,
+};
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
 use url::Url;
 
 use crate::{
-    db::{NewEntry, NewFeed, NewIcon},
-    feed_loader::{feed::parse_feed, html::Html},
+    db::{IconLookup, NewEntry, NewFeed, NewIcon},
+    feed_loader::{
+        charset::{decode_feed, decode_html},
+        extract::extract_article,
+        feed::parse_feed,
+        freshness::{freshness_lifetime, retry_after},
+        html::{Html, HtmlError},
+        icon::{
+            IconCandidate, IconSource, MAX_ICON_BYTES, manifest_icons, normalize_icon, rank_icons,
+        },
+        rewrite::site_feed_url,
+        sanitize::sanitize_html,
+        schedule::{posting_interval, sync_interval},
+        xml_base::resolve,
+    },
 };
 
+mod atom_dates;
+mod charset;
+mod date;
+mod enclosure;
+mod extract;
 mod feed;
+mod freshness;
+mod host_limit;
 mod html;
+mod icon;
+mod rdf;
+mod rewrite;
+mod robots;
+mod sanitize;
+mod schedule;
 mod sync;
+mod title;
+mod xml_base;
+pub use feed::FeedFormat;
+pub use host_limit::{HostLimits, interleave_hosts, set_host_limits};
+pub use sanitize::is_html;
+pub use schedule::{DEFAULT_SYNC_INTERVAL, SyncBounds};
 pub use sync::*;
 
 pub const SYNC_RESULT_SUCCESS: &str = "success";
 pub const SYNC_RESULT_NOT_MODIFIED: &str = "not_modified";
 pub const SYNC_RESULT_PARSE_ERROR: &str = "parse_error";
 pub const SYNC_RESULT_NOT_FOUND: &str = "not_found";
+pub const SYNC_RESULT_GONE: &str = "gone";
 pub const SYNC_RESULT_DISALLOWED: &str = "disallowed";
 pub const SYNC_RESULT_NEEDS_CHOICE: &str = "needs_choice";
 pub const SYNC_RESULT_UNEXPECTED_HTML: &str = "unexpected_html";
@@ -30,7 +70,7 @@ pub const SYNC_RESULT_DB_ERROR: &str = "db_error";
 pub fn sync_result_for_feed_result(result: &FeedResult) -> &'static str {
     match result {
         FeedResult::Loaded(_) => SYNC_RESULT_SUCCESS,
-        FeedResult::NotModified => SYNC_RESULT_NOT_MODIFIED,
+        FeedResult::NotModified { .. } => SYNC_RESULT_NOT_MODIFIED,
         FeedResult::NeedsChoice(_) => SYNC_RESULT_NEEDS_CHOICE,
         FeedResult::NotFound => SYNC_RESULT_NOT_FOUND,
         FeedResult::Disallowed => SYNC_RESULT_DISALLOWED,
@@ -39,10 +79,12 @@ pub fn sync_result_for_feed_result(result: &FeedResult) -> &'static str {
 
 pub fn sync_result_for_error(err: &FeedError) -> &'static str {
     match err {
-        FeedError::Parse => SYNC_RESULT_PARSE_ERROR,
+        FeedError::Parse | FeedError::Html(_) => SYNC_RESULT_PARSE_ERROR,
         FeedError::UnexpectedHtml => SYNC_RESULT_UNEXPECTED_HTML,
         FeedError::InvalidUrl => SYNC_RESULT_INVALID_URL,
         FeedError::NotFound => SYNC_RESULT_NOT_FOUND,
+        FeedError::Gone => SYNC_RESULT_GONE,
+        FeedError::RetryLater { .. } => SYNC_RESULT_FETCH_ERROR,
         FeedError::Fetch(fetch_err) => match fetch_err {
             FetchError::InvalidUrl => SYNC_RESULT_INVALID_URL,
             FetchError::Disallowed => SYNC_RESULT_DISALLOWED,
@@ -52,20 +94,43 @@ pub fn sync_result_for_error(err: &FeedError) -> &'static str {
     }
 }
 
+/// How a feed is loaded, beyond the cache headers of the last fetch.
+#[derive(Debug, Clone, Copy)]
+pub struct LoadOptions {
+    /// Can be turned off when the feed's icon was refreshed recently.
+    pub load_icon: bool,
+    /// Fetches the feed even where robots.txt disallows it, which the user
+    /// has to ask for.
+    pub ignore_robots: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            load_icon: true,
+            ignore_robots: false,
+        }
+    }
+}
+
 #[tracing::instrument(name = "load_feed", skip(etag, last_modified))]
 pub async fn load_feed(
     url: &str,
     etag: Option<String>,
     last_modified: Option<String>,
+    options: LoadOptions,
 ) -> Result<FeedResult, FeedError> {
-    let result = FeedLoader::new(url, etag, last_modified).run().await;
+    let result = FeedLoader::new(url, etag, last_modified)
+        .with_options(options)
+        .run()
+        .await;
     match &result {
         Ok(FeedResult::Loaded(loaded)) => {
             tracing::info!("loaded feed: {}", loaded.feed.title)
         }
-        Ok(FeedResult::NotModified) => tracing::info!("feed not modified"),
-        Ok(FeedResult::NeedsChoice(urls)) => {
-            tracing::info!("feed discovery found {} options", urls.len())
+        Ok(FeedResult::NotModified { .. }) => tracing::info!("feed not modified"),
+        Ok(FeedResult::NeedsChoice(candidates)) => {
+            tracing::info!("feed discovery found {} options", candidates.len())
         }
         Ok(FeedResult::NotFound) => tracing::warn!("feed not found"),
         Ok(FeedResult::Disallowed) => tracing::warn!("feed disallowed by robots.txt"),
@@ -79,8 +144,10 @@ pub async fn load_selected_feed(
     url: &str,
     etag: Option<String>,
     last_modified: Option<String>,
+    options: LoadOptions,
 ) -> Result<LoadedFeed, FeedError> {
     let result = FeedLoader::new_selected(url, etag, last_modified)
+        .with_options(options)
         .run()
         .await;
     match &result {
@@ -90,32 +157,346 @@ pub async fn load_selected_feed(
     result
 }
 
-const USER_AGENT: &str = "rss reader";
-static CLIENT: Lazy<Client> = Lazy::new(|| {
-    Client::builder()
-        .user_agent(USER_AGENT)
-        .redirect(redirect::Policy::limited(10))
-        .timeout(Duration::from_secs(10))
+/// Downloads an entry's page and extracts the article from it, for feeds that
+/// only ship a teaser.
+/// `ignore_robots` follows the entry's feed.
+#[tracing::instrument(name = "load_full_content")]
+pub async fn load_full_content(url: &str, ignore_robots: bool) -> Result<String, FeedError> {
+    let mut loader = FeedLoader::new(url, None, None).with_options(LoadOptions {
+        load_icon: false,
+        ignore_robots,
+    });
+    let url = loader.url.clone();
+    let response = loader
+        .do_fetch_with_headers(&url, None, None)
+        .await
+        .map_err(FeedError::Fetch)?;
+
+    match classify_response(response).await? {
+        Content::Html { bytes, final_url } => {
+            let article = extract_article(&bytes, &final_url).ok_or(FeedError::NoArticle)?;
+            tracing::debug!(bytes = article.len(), "extracted article");
+            Ok(sanitize_html(&article, &final_url))
+        }
+        Content::Feed { .. } => Err(FeedError::NoArticle),
+        Content::NotFound => Err(FeedError::NotFound),
+        Content::NotModified => Err(FeedError::NotFound), // no conditional headers were sent
+    }
+}
+
+/// Largest image the image proxy fetches.
+const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Image types the image proxy serves. SVG is left out since it can carry
+/// script and would be served from our origin.
+const PROXIED_IMAGE_TYPES: &[&str] = &[
+    "image/avif",
+    "image/bmp",
+    "image/gif",
+    "image/jpeg",
+    "image/png",
+    "image/vnd.microsoft.icon",
+    "image/webp",
+    "image/x-icon",
+];
+
+#[derive(Debug)]
+pub struct FetchedImage {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Fetches a remote image for the image proxy, refusing anything that isn't a
+/// raster image of at most `MAX_IMAGE_BYTES`, or that lives on a local
+/// address, whether linked directly or redirected to.
+#[tracing::instrument(name = "fetch_image")]
+pub async fn fetch_image(url: &str) -> Result<FetchedImage, FetchError> {
+    fetch_image_with(&IMAGE_CLIENT, url).await
+}
+
+async fn fetch_image_with(client: &Client, url: &str) -> Result<FetchedImage, FetchError> {
+    let parsed = Url::parse(url).map_err(|_| FetchError::InvalidUrl)?;
+    if !is_public_url(&parsed) {
+        return Err(FetchError::InvalidUrl);
+    }
+
+    let response = client.get(parsed).send().await?;
+    if response.status() != StatusCode::OK {
+        return Err(FetchError::Status(response.status()));
+    }
+
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    if !PROXIED_IMAGE_TYPES.contains(&content_type.as_str()) {
+        return Err(FetchError::ContentType(content_type));
+    }
+
+    let data = read_body(response, MAX_IMAGE_BYTES).await?;
+
+    tracing::debug!(bytes = data.len(), content_type, "fetched image");
+    Ok(FetchedImage { content_type, data })
+}
+
+/// Reads a response body, giving up as soon as it is known to be larger than
+/// `max_bytes`. Takes the response so the host's request slot is released as
+/// soon as the body is read.
+async fn read_body(mut response: Response, max_bytes: usize) -> Result<Vec<u8>, FetchError> {
+    if response
+        .content_length()
+        .is_some_and(|len| len > max_bytes as u64)
+    {
+        return Err(FetchError::TooLarge);
+    }
+
+    let mut data = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if data.len() + chunk.len() > max_bytes {
+            return Err(FetchError::TooLarge);
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data)
+}
+
+/// An http(s) URL on a public host.
+fn is_public_url(url: &Url) -> bool {
+    matches!(url.scheme(), "http" | "https") && is_public_host(url)
+}
+
+/// Whether the URL points outside of our network, judging by its host. Host
+/// names resolving to local addresses are left to [`PublicResolver`].
+fn is_public_host(url: &Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.');
+            domain != "localhost" && !domain.ends_with(".localhost") && !domain.ends_with(".local")
+        }
+        Some(url::Host::Ipv4(ip)) => is_public_ip(ip.into()),
+        Some(url::Host::Ipv6(ip)) => is_public_ip(ip.into()),
+        None => false,
+    }
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // carrier-grade NAT, 100.64.0.0/10
+                || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => {
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()
+                || ip.to_ipv4_mapped().is_some())
+        }
+    }
+}
+
+/// Resolves host names for the image proxy, leaving out local addresses, so
+/// that a name pointing into our network can't be used to reach it. Every
+/// connection goes through it, redirects included.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} only resolves to local addresses", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Paths sites commonly serve their feed from, probed when a page doesn't
+/// link to one.
+const WELL_KNOWN_FEED_PATHS: &[&str] = &["/feed", "/rss.xml", "/atom.xml", "/index.xml"];
+
+/// Limits the requests spent on body links that look like feeds.
+const MAX_PROBED_BODY_LINKS: usize = 3;
+
+/// Redirects followed for a single request, as many as browsers allow.
+const MAX_REDIRECTS: usize = 10;
+
+pub const DEFAULT_USER_AGENT: &str = "rss-reader";
+
+/// Guards against responses that trickle in just fast enough to never hit the
+/// read timeout.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// How requests to sites are made.
+#[derive(Debug, Clone)]
+pub struct HttpOptions {
+    /// Also the name robots.txt rules are looked up by, by its first word.
+    pub user_agent: String,
+    pub connect_timeout: Duration,
+    /// Longest wait for the next bit of a response.
+    pub read_timeout: Duration,
+    /// Every request goes through it when set, e.g. `socks5h://127.0.0.1:9050`.
+    pub proxy: Option<Proxy>,
+    /// Largest feed or page read, counted as it's downloaded and after
+    /// decompression.
+    pub max_response_bytes: usize,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            proxy: None,
+            max_response_bytes: 10 * 1024 * 1024,
+        }
+    }
+}
+
+static HTTP_OPTIONS: OnceCell<HttpOptions> = OnceCell::new();
+
+/// Sets how every request is made. It has to be called before the first
+/// request.
+pub fn set_http_options(options: HttpOptions) {
+    if HTTP_OPTIONS.set(options).is_err() {
+        tracing::warn!("http options already set");
+    }
+}
+
+fn http_options() -> &'static HttpOptions {
+    HTTP_OPTIONS.get_or_init(HttpOptions::default)
+}
+
+fn user_agent() -> &'static str {
+    &http_options().user_agent
+}
+
+static CLIENT: Lazy<Client> = Lazy::new(|| build_client(redirect::Policy::limited(MAX_REDIRECTS)));
+
+/// Only connects to public addresses and follows redirects to public hosts,
+/// so a remote image can't point the image proxy at our own network.
+static IMAGE_CLIENT: Lazy<Client> = Lazy::new(|| {
+    client_builder(image_redirect_policy())
+        .dns_resolver(Arc::new(PublicResolver))
         .build()
         .expect("client should be valid")
 });
 
+fn image_redirect_policy() -> redirect::Policy {
+    redirect::Policy::custom(|attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if !is_public_url(attempt.url()) {
+            attempt.error("redirect to a non-public address")
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+/// Leaves redirects to `FeedLoader`, which checks robots.txt at each hop and
+/// notices when a feed has moved.
+static LOADER_CLIENT: Lazy<Client> = Lazy::new(|| build_client(redirect::Policy::none()));
+
+fn build_client(redirect: redirect::Policy) -> Client {
+    client_builder(redirect)
+        .build()
+        .expect("client should be valid")
+}
+
+fn client_builder(redirect: redirect::Policy) -> ClientBuilder {
+    let options = http_options();
+    let mut builder = Client::builder()
+        .user_agent(user_agent())
+        .redirect(redirect)
+        .connect_timeout(options.connect_timeout)
+        .read_timeout(options.read_timeout)
+        .timeout(REQUEST_TIMEOUT)
+        .gzip(true)
+        .brotli(true);
+    if let Some(proxy) = &options.proxy {
+        builder = builder.proxy(proxy.clone());
+    }
+    builder
+}
+
 #[derive(Debug)]
 pub enum FeedResult {
-    Loaded(LoadedFeed),
-    NotModified,
-    NeedsChoice(Vec<String>),
+    Loaded(Box<LoadedFeed>),
+    NotModified {
+        /// Where the feed permanently redirected to before answering, as for
+        /// [`LoadedFeed::moved_to`].
+        moved_to: Option<String>,
+    },
+    NeedsChoice(Vec<FeedCandidate>),
     NotFound,
     Disallowed,
 }
 
+/// One of several feeds found for a page, described so the user can pick.
+/// The details are unknown when the candidate couldn't be fetched or parsed.
+#[derive(Debug, serde::Serialize)]
+pub struct FeedCandidate {
+    pub url: String,
+    pub title: Option<String>,
+    pub format: Option<FeedFormat>,
+    pub entry_count: Option<usize>,
+}
+
+impl FeedCandidate {
+    fn unprobed(url: String) -> Self {
+        Self {
+            url,
+            title: None,
+            format: None,
+            entry_count: None,
+        }
+    }
+
+    fn is_probed(&self) -> bool {
+        self.format.is_some()
+    }
+}
+
 #[derive(Debug)]
 pub struct LoadedFeed {
     pub feed: NewFeed,
     pub entries: Vec<NewEntry>,
-    pub icon: Option<NewIcon>,
+    pub icon: IconLookup,
     pub http_etag: Option<String>,
     pub http_last_modified: Option<String>,
+    /// What the feed says about how often to poll it.
+    pub update_interval: Option<Duration>,
+    /// How long the response may be cached for.
+    pub freshness: Option<Duration>,
+    /// Where `feed.feed_url` permanently redirected to.
+    pub moved_to: Option<String>,
+}
+
+impl LoadedFeed {
+    /// How long to wait before syncing the feed again, judging by how often
+    /// it posts and what its publisher asks for.
+    pub fn sync_interval(&self, bounds: &SyncBounds) -> Duration {
+        let posting = posting_interval(
+            self.entries.iter().filter_map(|entry| entry.published_at),
+            chrono::Utc::now(),
+        );
+        sync_interval(posting, &[self.update_interval, self.freshness], bounds)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -129,14 +510,41 @@ pub enum FeedError {
     #[error("got unexpected response: {0}")]
     UnexpectedResponse(#[from] reqwest::Error),
 
+    /// `429 Too Many Requests` or `503 Service Unavailable`.
+    #[error("server asked to retry later: {status}")]
+    RetryLater {
+        status: StatusCode,
+        retry_after: Option<Duration>,
+    },
+
     #[error("failed to parse feed")]
     Parse,
 
     #[error("expected feed but got html")]
     UnexpectedHtml,
 
+    #[error("failed to parse html: {0}")]
+    Html(#[from] HtmlError),
+
     #[error("not found")]
     NotFound,
+
+    /// `410 Gone`: the feed was removed for good.
+    #[error("gone")]
+    Gone,
+
+    #[error("no article found in page")]
+    NoArticle,
+}
+
+impl FeedError {
+    /// How long the server asked us to wait before trying again.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            FeedError::RetryLater { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -153,8 +561,17 @@ pub enum FetchError {
     #[error("error fetching robots.txt")]
     RobotsFetchFailed,
 
-    #[error("error parsing robots.txt")]
-    RobotsParseFailed,
+    #[error("unexpected status {0}")]
+    Status(StatusCode),
+
+    #[error("unexpected content type {0:?}")]
+    ContentType(String),
+
+    #[error("response too large")]
+    TooLarge,
+
+    #[error("too many redirects")]
+    TooManyRedirects,
 }
 
 impl From<reqwest::Error> for FetchError {
@@ -169,6 +586,7 @@ enum Content {
         final_url: Url,
         etag: Option<String>,
         last_modified: Option<String>,
+        freshness: Option<Duration>,
     },
     Html {
         bytes: Vec<u8>,
@@ -221,6 +639,7 @@ struct FetchedFeed {
     final_url: Url,
     etag: Option<String>,
     last_modified: Option<String>,
+    freshness: Option<Duration>,
 }
 
 struct ParsedFeed {
@@ -229,37 +648,47 @@ struct ParsedFeed {
     final_url: Url,
     etag: Option<String>,
     last_modified: Option<String>,
+    freshness: Option<Duration>,
 }
 
 struct FeedMeta {
     title: String,
     site_url: Option<String>,
+    image_url: Option<String>,
+    update_interval: Option<Duration>,
 }
 
 enum Fetched {
     Feed(FeedLoader<FetchedFeed>),
     Html(FeedLoader<FetchedHtml>),
     NotFound,
-    NotModified,
+    NotModified { moved_to: Option<String> },
 }
 
 enum SelectedFetched {
-    Feed(FeedLoader<FetchedFeed>),
+    Feed(Box<FeedLoader<FetchedFeed>>),
     NotFound,
     NotModified,
 }
 
 struct FeedLoader<S> {
-    robots: HashMap<String, Robot>,
     url: String,
+    /// Whether to look for the feed's icon once it's loaded.
+    load_icon: bool,
+    /// Skips robots.txt for every request, icons included.
+    ignore_robots: bool,
+    /// Where fetching `url` was permanently redirected to.
+    moved_to: Option<String>,
     state: S,
 }
 
 impl FeedLoader<Initial> {
     fn new(url: &str, etag: Option<String>, last_modified: Option<String>) -> Self {
         Self {
-            robots: HashMap::new(),
             url: ensure_scheme(url),
+            load_icon: true,
+            ignore_robots: false,
+            moved_to: None,
             state: Initial {
                 etag,
                 last_modified,
@@ -268,10 +697,17 @@ impl FeedLoader<Initial> {
     }
 
     async fn run(self) -> Result<FeedResult, FeedError> {
-        match self.fetch().await? {
+        let fetched = match self.fetch().await {
+            Err(FeedError::Fetch(FetchError::Disallowed)) => return Ok(FeedResult::Disallowed),
+            fetched => fetched?,
+        };
+        match fetched {
             Fetched::NotFound => Ok(FeedResult::NotFound),
-            Fetched::NotModified => Ok(FeedResult::NotModified),
-            Fetched::Feed(loader) => loader.run().await.map(FeedResult::Loaded),
+            Fetched::NotModified { moved_to } => Ok(FeedResult::NotModified { moved_to }),
+            Fetched::Feed(loader) => loader
+                .run()
+                .await
+                .map(|loaded| FeedResult::Loaded(Box::new(loaded))),
             Fetched::Html(loader) => loader.run().await,
         }
     }
@@ -282,12 +718,15 @@ impl FeedLoader<Initial> {
 
         Ok(match classify_response(response).await? {
             Content::NotFound => Fetched::NotFound,
-            Content::NotModified => Fetched::NotModified,
+            Content::NotModified => Fetched::NotModified {
+                moved_to: self.moved_to,
+            },
             Content::Feed {
                 bytes,
                 final_url,
                 etag,
                 last_modified,
+                freshness,
             } => {
                 tracing::debug!(bytes = bytes.len(), %final_url, "fetched feed");
                 Fetched::Feed(self.into_state(FetchedFeed {
@@ -295,6 +734,7 @@ impl FeedLoader<Initial> {
                     final_url,
                     etag,
                     last_modified,
+                    freshness,
                 }))
             }
             Content::Html { bytes, final_url } => {
@@ -308,8 +748,10 @@ impl FeedLoader<Initial> {
 impl FeedLoader<Selected> {
     fn new_selected(url: &str, etag: Option<String>, last_modified: Option<String>) -> Self {
         Self {
-            robots: HashMap::new(),
             url: ensure_scheme(url),
+            load_icon: true,
+            ignore_robots: false,
+            moved_to: None,
             state: Selected {
                 etag,
                 last_modified,
@@ -335,14 +777,18 @@ impl FeedLoader<Selected> {
                 final_url,
                 etag,
                 last_modified,
+                freshness,
             } => {
                 tracing::debug!(bytes = bytes.len(), "fetched selected feed");
-                Ok(SelectedFetched::Feed(self.into_state(FetchedFeed {
-                    bytes,
-                    final_url,
-                    etag,
-                    last_modified,
-                })))
+                Ok(SelectedFetched::Feed(Box::new(self.into_state(
+                    FetchedFeed {
+                        bytes,
+                        final_url,
+                        etag,
+                        last_modified,
+                        freshness,
+                    },
+                ))))
             }
             Content::Html { .. } => Err(FeedError::UnexpectedHtml),
             Content::NotFound => Ok(SelectedFetched::NotFound),
@@ -352,43 +798,120 @@ impl FeedLoader<Selected> {
 }
 
 impl FeedLoader<FetchedHtml> {
-    async fn run(self) -> Result<FeedResult, FeedError> {
-        let feed_urls = self.discover_feeds();
+    async fn run(mut self) -> Result<FeedResult, FeedError> {
+        let mut candidates = self.discover_feeds().await?;
 
-        match feed_urls.as_slice() {
+        match candidates.as_slice() {
             [] => Ok(FeedResult::NotFound),
-            [single_url] => self
-                .select(single_url.to_owned())
-                .run()
-                .await
-                .map(FeedResult::Loaded),
-            _ => Ok(FeedResult::NeedsChoice(feed_urls)),
+            [single] => {
+                let url = single.url.clone();
+                self.select(url)
+                    .run()
+                    .await
+                    .map(|loaded| FeedResult::Loaded(Box::new(loaded)))
+            }
+            _ => {
+                for candidate in candidates.iter_mut().filter(|c| !c.is_probed()) {
+                    if let Some(probed) = self.probe_feed(&candidate.url).await {
+                        *candidate = probed;
+                    }
+                }
+                Ok(FeedResult::NeedsChoice(candidates))
+            }
         }
     }
 
-    fn discover_feeds(&self) -> Vec<String> {
-        let origin = self.state.final_url.origin().ascii_serialization();
-        let html = Html::from_bytes(&self.state.bytes);
+    /// Feeds the site advertises or is known to have. Failing those, body
+    /// links that look like feeds and common feed paths. Everything but the
+    /// advertised feeds is a guess, including the feed the URL pattern of a
+    /// known site points to, and only kept if it turns out to be a feed.
+    async fn discover_feeds(&mut self) -> Result<Vec<FeedCandidate>, FeedError> {
+        let (advertised, linked) = {
+            let html = Html::from_bytes(&self.state.bytes)?;
+            let base = html.base_url(&self.state.final_url);
+
+            let advertised: Vec<String> = html
+                .feed_urls()
+                .iter()
+                .map(|href| absolutize(href, &base))
+                .collect();
+            let linked: Vec<String> = html
+                .body_feed_urls()
+                .iter()
+                .map(|href| absolutize(href, &base))
+                .collect();
+            (advertised, linked)
+        };
 
-        let feed_urls: Vec<String> = html
-            .feed_urls()
-            .iter()
-            .map(|href| absolutize(href, &origin))
-            .collect();
+        let mut candidates: Vec<FeedCandidate> = Vec::new();
+        if let Some(url) = site_feed_url(&self.state.final_url)
+            && let Some(candidate) = self.probe_feed(&url).await
+        {
+            candidates.push(candidate);
+        }
+        for url in advertised {
+            if !candidates.iter().any(|c| c.url == url) {
+                candidates.push(FeedCandidate::unprobed(url));
+            }
+        }
+
+        if candidates.is_empty() {
+            for url in linked.into_iter().take(MAX_PROBED_BODY_LINKS) {
+                if candidates.iter().any(|c| c.url == url) {
+                    continue;
+                }
+                if let Some(candidate) = self.probe_feed(&url).await {
+                    candidates.push(candidate);
+                }
+            }
+        }
+
+        if candidates.is_empty() {
+            for path in WELL_KNOWN_FEED_PATHS {
+                let url = absolutize(path, &self.state.final_url);
+                // these usually all serve the same feed
+                if let Some(candidate) = self.probe_feed(&url).await {
+                    candidates.push(candidate);
+                    break;
+                }
+            }
+        }
 
         tracing::debug!(
-            count = feed_urls.len(),
-            ?feed_urls,
+            count = candidates.len(),
+            feed_urls = ?candidates.iter().map(|c| &c.url).collect::<Vec<_>>(),
             "discovered feeds from html"
         );
 
-        feed_urls
+        Ok(candidates)
+    }
+
+    /// Fetches and parses `url`, describing it if it is a feed.
+    async fn probe_feed(&mut self, url: &str) -> Option<FeedCandidate> {
+        let response = self.do_fetch_with_headers(url, None, None).await.ok()?;
+        let candidate = match classify_response(response).await {
+            Ok(Content::Feed {
+                bytes, final_url, ..
+            }) => parse_feed(&bytes, &final_url)
+                .ok()
+                .map(|(feed, entries)| FeedCandidate {
+                    url: url.to_owned(),
+                    title: Some(feed.title).filter(|title| !title.is_empty()),
+                    format: Some(feed.format),
+                    entry_count: Some(entries.len()),
+                }),
+            _ => None,
+        };
+        tracing::trace!(url, is_feed = candidate.is_some(), "probed for feed");
+        candidate
     }
 
     fn select(self, feed_url: String) -> FeedLoader<Selected> {
         FeedLoader {
-            robots: self.robots,
             url: feed_url,
+            load_icon: self.load_icon,
+            ignore_robots: self.ignore_robots,
+            moved_to: None,
             state: Selected {
                 etag: None,
                 last_modified: None,
@@ -403,37 +926,52 @@ impl FeedLoader<FetchedFeed> {
     }
 
     fn parse(self) -> Result<FeedLoader<ParsedFeed>, FeedError> {
-        let (meta, entries) = parse_feed(&self.state.bytes, &self.url).map_err(|e| {
-            tracing::debug!("failed to parse feed: {:?}", e);
-            FeedError::Parse
-        })?;
+        let (meta, entries) =
+            parse_feed(&self.state.bytes, &self.state.final_url).map_err(|e| {
+                tracing::debug!("failed to parse feed: {:?}", e);
+                FeedError::Parse
+            })?;
 
         tracing::debug!(title = meta.title, entries = entries.len(), "parsed feed");
+        for diagnostic in &meta.diagnostics {
+            tracing::warn!(url = self.url, "{diagnostic}");
+        }
 
         let final_url = self.state.final_url.to_owned();
         let etag = self.state.etag.clone();
         let last_modified = self.state.last_modified.clone();
+        let freshness = self.state.freshness;
 
         Ok(self.into_state(ParsedFeed {
             meta: FeedMeta {
                 title: meta.title,
                 site_url: meta.site_url,
+                image_url: meta.image_url,
+                update_interval: meta.update_interval,
             },
             entries,
             final_url,
             etag,
             last_modified,
+            freshness,
         }))
     }
 }
 
 impl FeedLoader<ParsedFeed> {
     async fn run(mut self) -> Result<LoadedFeed, FeedError> {
-        let icon = self.load_favicon().await;
+        let icon = if self.load_icon {
+            match self.load_favicon().await {
+                Some(icon) => IconLookup::Found(icon),
+                None => IconLookup::NotFound,
+            }
+        } else {
+            IconLookup::Skipped
+        };
         Ok(self.finish(icon))
     }
 
-    fn finish(self, icon: Option<NewIcon>) -> LoadedFeed {
+    fn finish(self, icon: IconLookup) -> LoadedFeed {
         LoadedFeed {
             feed: NewFeed {
                 title: self.state.meta.title,
@@ -444,10 +982,23 @@ impl FeedLoader<ParsedFeed> {
             icon,
             http_etag: self.state.etag,
             http_last_modified: self.state.last_modified,
+            update_interval: self.state.meta.update_interval,
+            freshness: self.state.freshness,
+            moved_to: self.moved_to,
         }
     }
 
+    /// Tries the feed's own image, then the icons the site declares, best
+    /// first, then `/favicon.ico`.
     async fn load_favicon(&mut self) -> Option<NewIcon> {
+        let mut candidates: Vec<IconCandidate> = self
+            .state
+            .meta
+            .image_url
+            .iter()
+            .map(|url| IconCandidate::new(url.to_owned(), IconSource::Feed))
+            .collect();
+
         let origin = self
             .state
             .meta
@@ -459,21 +1010,13 @@ impl FeedLoader<ParsedFeed> {
                     .map(|url| url.origin().ascii_serialization())
             })
             .unwrap_or_else(|| self.state.final_url.origin().ascii_serialization());
+        candidates.extend(self.site_icons(&origin).await);
 
-        let response = self.do_fetch_with_headers(&origin, None, None).await.ok()?;
-        let bytes = response.bytes().await.ok()?;
-        let favicon_urls = {
-            let html = Html::from_bytes(&bytes);
-
-            let mut favicon_urls = html.favicon_urls();
-            favicon_urls.push(format!("{origin}/favicon.ico"));
-            favicon_urls
-        };
-
-        tracing::debug!(candidates = favicon_urls.len(), "looking for favicon");
+        let candidates = rank_icons(candidates);
+        tracing::debug!(candidates = candidates.len(), "looking for favicon");
 
-        for href in favicon_urls {
-            let url = absolutize(&href, &origin);
+        for candidate in candidates {
+            let url = candidate.url;
             tracing::trace!("trying favicon url: {}", url);
 
             if let Some(icon) = parse_data_url(&url) {
@@ -491,59 +1034,116 @@ impl FeedLoader<ParsedFeed> {
         None
     }
 
+    /// Icons linked from the site's home page and its web app manifest, and
+    /// the `/favicon.ico` fallback.
+    async fn site_icons(&mut self, origin: &str) -> Vec<IconCandidate> {
+        let fallback = IconCandidate::new(format!("{origin}/favicon.ico"), IconSource::Fallback);
+        let mut candidates = vec![fallback];
+
+        let Ok(response) = self.do_fetch_with_headers(origin, None, None).await else {
+            return candidates;
+        };
+        let page_url = response.url().to_owned();
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        let Ok(bytes) = read_body(response, http_options().max_response_bytes).await else {
+            return candidates;
+        };
+        let bytes = decode_html(bytes, &content_type);
+
+        let manifest_url = {
+            let html = match Html::from_bytes(&bytes) {
+                Ok(html) => html,
+                Err(e) => {
+                    tracing::debug!("failed to parse site page: {e}");
+                    return candidates;
+                }
+            };
+            let base = html.base_url(&page_url);
+
+            candidates.extend(html.icon_links().into_iter().map(|link| {
+                IconCandidate::from_link(
+                    absolutize(&link.href, &base),
+                    &link.rel,
+                    link.sizes.as_deref(),
+                    link.link_type.as_deref(),
+                )
+            }));
+            html.manifest_url()
+                .and_then(|href| base.join(href.trim()).ok())
+        };
+
+        if let Some(manifest_url) = manifest_url
+            && let Some(bytes) = self.fetch_limited(manifest_url.as_str()).await
+        {
+            candidates.extend(manifest_icons(&bytes, &manifest_url));
+        }
+
+        candidates
+    }
+
     async fn fetch_icon(&mut self, url: &str) -> Option<NewIcon> {
         if !url.starts_with("http") {
             tracing::trace!("skipping non-http favicon url: {}", url);
             return None;
         }
 
+        let bytes = self.fetch_limited(url).await?;
+        icon_from_bytes(&bytes)
+    }
+
+    /// The body of a successful response, unless it's over `MAX_ICON_BYTES`.
+    async fn fetch_limited(&mut self, url: &str) -> Option<Vec<u8>> {
         let response = self.do_fetch_with_headers(url, None, None).await.ok()?;
 
         if response.status() != StatusCode::OK {
-            tracing::trace!("favicon fetch failed with status: {}", response.status());
+            tracing::trace!("icon fetch failed with status: {}", response.status());
             return None;
         }
 
-        let content_type = response
-            .headers()
-            .get("content-type")?
-            .to_str()
-            .ok()?
-            .to_owned();
-
-        if !content_type.starts_with("image/") {
-            tracing::trace!("favicon has non-image content-type: {}", content_type);
-            return None;
+        match read_body(response, MAX_ICON_BYTES).await {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                tracing::trace!("failed to read icon response: {e}");
+                None
+            }
         }
-
-        let bytes = response.bytes().await.ok()?;
-        tracing::trace!(
-            "fetched favicon ({} bytes, type: {})",
-            bytes.len(),
-            content_type
-        );
-
-        Some(NewIcon {
-            hash: hash_bytes(&bytes),
-            data: bytes.to_vec(),
-            content_type,
-        })
     }
 }
 
 impl<S: HasConditionalHeaders> FeedLoader<S> {
+    /// Fetches the feed itself, remembering whether it has moved.
     async fn do_fetch(&mut self, url: &str) -> Result<Response, FetchError> {
         let etag = self.state.etag().map(|s| s.to_owned());
         let last_modified = self.state.last_modified().map(|s| s.to_owned());
-        self.do_fetch_with_headers(url, etag, last_modified).await
+        let (response, moved_to) = self
+            .send_following_redirects(url, etag, last_modified)
+            .await?;
+        if let Some(moved_to) = &moved_to {
+            tracing::debug!(%moved_to, "feed moved permanently");
+        }
+        self.moved_to = moved_to.map(String::from);
+        Ok(response)
     }
 }
 
 impl<S> FeedLoader<S> {
+    fn with_options(mut self, options: LoadOptions) -> Self {
+        self.load_icon = options.load_icon;
+        self.ignore_robots = options.ignore_robots;
+        self
+    }
+
     fn into_state<T>(self, state: T) -> FeedLoader<T> {
         FeedLoader {
-            robots: self.robots,
             url: self.url,
+            load_icon: self.load_icon,
+            ignore_robots: self.ignore_robots,
+            moved_to: self.moved_to,
             state,
         }
     }
@@ -554,64 +1154,92 @@ impl<S> FeedLoader<S> {
         etag: Option<String>,
         last_modified: Option<String>,
     ) -> Result<Response, FetchError> {
-        if !self.is_allowed(url).await? {
-            return Err(FetchError::Disallowed);
-        }
-
-        let has_conditional = etag.is_some() || last_modified.is_some();
-        let mut request = CLIENT.get(url);
-
-        if let Some(ref etag) = etag {
-            request = request.header("If-None-Match", etag);
-        }
-        if let Some(ref last_modified) = last_modified {
-            request = request.header("If-Modified-Since", last_modified);
-        }
-
-        let response = request.send().await.map_err(FetchError::Network)?;
-        tracing::debug!(
-            status = %response.status(),
-            conditional = has_conditional,
-            "fetched {url}"
-        );
-
+        let (response, _) = self
+            .send_following_redirects(url, etag, last_modified)
+            .await?;
         Ok(response)
     }
 
-    async fn is_allowed(&mut self, _url: &str) -> Result<bool, FetchError> {
-        Ok(true)
-        // let origin = Url::parse(url)
-        //     .map_err(|_| FetchError::InvalidUrl)?
-        //     .origin()
-        //     .ascii_serialization();
+    /// Follows redirects, checking robots.txt before each request. Also
+    /// returns where `url` moved to for good, which is as far as the chain of
+    /// redirects is permanent (301 or 308) from the start.
+    async fn send_following_redirects(
+        &self,
+        url: &str,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    ) -> Result<(Response, Option<Url>), FetchError> {
+        let mut url = Url::parse(url).map_err(|_| FetchError::InvalidUrl)?;
+        let mut moved_to = None;
+        let mut permanent = true;
+
+        for _ in 0..=MAX_REDIRECTS {
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(FetchError::InvalidUrl);
+            }
+            if !self.is_allowed(&url).await? {
+                return Err(FetchError::Disallowed);
+            }
 
-        // if let Some(robot) = self.robots.get(&origin) {
-        //     let allowed = robot.allowed(url);
-        //     tracing::trace!("robots.txt check (cached): {url} -> {allowed}");
-        //     return Ok(allowed);
-        // }
+            let has_conditional = etag.is_some() || last_modified.is_some();
+            let mut request = LOADER_CLIENT.get(url.clone());
 
-        // let robots_url = get_robots_url(url).map_err(|_| FetchError::InvalidUrl)?;
-        // tracing::trace!("fetching robots.txt from: {robots_url}");
+            if let Some(ref etag) = etag {
+                request = request.header("If-None-Match", etag);
+            }
+            if let Some(ref last_modified) = last_modified {
+                request = request.header("If-Modified-Since", last_modified);
+            }
 
-        // let robotstxt = CLIENT
-        //     .get(robots_url)
-        //     .send()
-        //     .await
-        //     .map_err(|_| FetchError::RobotsFetchFailed)?
-        //     .bytes()
-        //     .await
-        //     .map_err(|_| FetchError::RobotsParseFailed)?;
+            let response = host_limit::send(&url, request)
+                .await
+                .map_err(FetchError::Network)?;
+            tracing::debug!(
+                status = %response.status(),
+                conditional = has_conditional,
+                "fetched {url}"
+            );
+
+            let status = response.status();
+            let Some(location) = redirect_target(status, response.headers(), &url) else {
+                return Ok((response, moved_to));
+            };
+            permanent &= matches!(
+                status,
+                StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT
+            );
+            if permanent {
+                moved_to = Some(location.clone());
+            }
+            url = location;
+        }
 
-        // let robot =
-        //     Robot::new(USER_AGENT, &robotstxt).map_err(|_| FetchError::RobotsParseFailed)?;
+        Err(FetchError::TooManyRedirects)
+    }
 
-        // let allowed = robot.allowed(url);
-        // tracing::trace!("robots.txt check: {} -> {}", url, allowed);
-        // self.robots.insert(origin, robot);
+    async fn is_allowed(&self, url: &Url) -> Result<bool, FetchError> {
+        if self.ignore_robots {
+            return Ok(true);
+        }
+        robots::is_allowed(url).await
+    }
+}
 
-        // Ok(allowed)
+/// Where a redirect response points to, resolved against the URL it came
+/// from. Other responses, including `304 Not Modified`, have none.
+fn redirect_target(status: StatusCode, headers: &HeaderMap, url: &Url) -> Option<Url> {
+    if !matches!(
+        status,
+        StatusCode::MOVED_PERMANENTLY
+            | StatusCode::FOUND
+            | StatusCode::SEE_OTHER
+            | StatusCode::TEMPORARY_REDIRECT
+            | StatusCode::PERMANENT_REDIRECT
+    ) {
+        return None;
     }
+    let location = headers.get(header::LOCATION)?.to_str().ok()?;
+    url.join(location.trim()).ok()
 }
 
 async fn classify_response(response: Response) -> Result<Content, FeedError> {
@@ -620,7 +1248,14 @@ async fn classify_response(response: Response) -> Result<Content, FeedError> {
 
     match status {
         StatusCode::NOT_FOUND => Ok(Content::NotFound),
+        StatusCode::GONE => Err(FeedError::Gone),
         StatusCode::NOT_MODIFIED => Ok(Content::NotModified),
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => {
+            Err(FeedError::RetryLater {
+                status,
+                retry_after: retry_after(response.headers()),
+            })
+        }
 
         StatusCode::OK => {
             let headers = response.headers();
@@ -638,20 +1273,26 @@ async fn classify_response(response: Response) -> Result<Content, FeedError> {
                 .get("last-modified")
                 .and_then(|v| v.to_str().ok())
                 .map(|s| s.to_string());
+            let freshness = freshness_lifetime(headers);
 
-            let bytes = response.bytes().await?.to_vec();
+            let bytes = read_body(response, http_options().max_response_bytes)
+                .await
+                .map_err(FeedError::Fetch)?;
 
             if content_type.starts_with("text/html") {
+                let bytes = decode_html(bytes, &content_type);
                 Ok(Content::Html { bytes, final_url })
             } else {
                 if !is_feed_content_type(&content_type) {
                     tracing::debug!("unknown content-type '{content_type}', assuming feed");
                 }
+                let bytes = decode_feed(bytes, &content_type);
                 Ok(Content::Feed {
                     bytes,
                     final_url,
                     etag,
                     last_modified,
+                    freshness,
                 })
             }
         }
@@ -667,6 +1308,8 @@ fn is_feed_content_type(content_type: &str) -> bool {
         || content_type.starts_with("application/xml")
         || content_type.starts_with("application/rss+xml")
         || content_type.starts_with("application/atom+xml")
+        || content_type.starts_with("application/feed+json")
+        || content_type.starts_with("application/json")
 }
 
 fn ensure_scheme(url: &str) -> String {
@@ -677,15 +1320,13 @@ fn ensure_scheme(url: &str) -> String {
     }
 }
 
-fn absolutize(href: &str, origin: &str) -> String {
-    if href.starts_with("http") || href.starts_with("data:") {
-        href.to_owned()
+/// Resolves `href` against `base` the way a browser would, so `../` paths and
+/// protocol-relative `//cdn` URLs work. `data:` URLs are kept as they are.
+fn absolutize(href: &str, base: &Url) -> String {
+    if href.trim_start().starts_with("data:") {
+        href.trim().to_owned()
     } else {
-        format!(
-            "{}/{}",
-            origin.trim_end_matches('/'),
-            href.trim_start_matches('/')
-        )
+        resolve(base, href)
     }
 }
 
@@ -693,33 +1334,306 @@ fn parse_data_url(url: &str) -> Option<NewIcon> {
     use base64::Engine;
     use percent_encoding::percent_decode_str;
 
-    if !url.starts_with("data:") {
-        return None;
-    }
-
-    let (header, content) = url.split_once(',')?;
-    let content_type = header.split(':').nth(1)?.split(';').next()?;
+    let (header, content) = url.strip_prefix("data:")?.split_once(',')?;
 
     let data = if header.contains("base64") {
         base64::engine::general_purpose::STANDARD
             .decode(content)
             .ok()?
     } else {
-        percent_decode_str(content)
-            .decode_utf8()
-            .ok()?
-            .as_bytes()
-            .to_vec()
+        percent_decode_str(content).collect()
     };
+    if data.len() > MAX_ICON_BYTES {
+        return None;
+    }
 
-    Some(NewIcon {
-        hash: hash_bytes(&data),
-        data,
-        content_type: content_type.to_owned(),
-    })
+    icon_from_bytes(&data)
+}
+
+/// Stores icons as small PNGs, whatever they were served as.
+fn icon_from_bytes(bytes: &[u8]) -> Option<NewIcon> {
+    match normalize_icon(bytes) {
+        Ok(data) => Some(NewIcon {
+            hash: hash_bytes(&data),
+            data,
+            content_type: "image/png".to_string(),
+        }),
+        Err(e) => {
+            tracing::trace!("failed to decode icon: {e}");
+            None
+        }
+    }
 }
 
 fn hash_bytes(bytes: &[u8]) -> String {
     use sha2::{Digest, Sha256};
     format!("{:x}", Sha256::digest(bytes))
 }
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn location(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::LOCATION, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn resolves_redirect_targets() {
+        let url = Url::parse("https://example.com/blog/feed.xml").unwrap();
+
+        assert_eq!(
+            redirect_target(StatusCode::MOVED_PERMANENTLY, &location("/rss"), &url)
+                .map(String::from),
+            Some("https://example.com/rss".to_string())
+        );
+        assert_eq!(
+            redirect_target(
+                StatusCode::FOUND,
+                &location("https://feeds.example.net/blog"),
+                &url
+            )
+            .map(String::from),
+            Some("https://feeds.example.net/blog".to_string())
+        );
+        assert_eq!(
+            redirect_target(StatusCode::NOT_MODIFIED, &location("/rss"), &url),
+            None
+        );
+        assert_eq!(
+            redirect_target(StatusCode::PERMANENT_REDIRECT, &HeaderMap::new(), &url),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn caps_response_bodies() {
+        let response = |body: &'static str| Response::from(axum::http::Response::new(body));
+
+        assert_eq!(read_body(response("feed"), 4).await.unwrap(), b"feed");
+        assert!(matches!(
+            read_body(response("feeds"), 4).await,
+            Err(FetchError::TooLarge)
+        ));
+    }
+
+    /// Serves `respond(port, request)` for each request on a local port.
+    async fn serve(respond: fn(u16, &str) -> String) -> u16 {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0; 1024];
+                    let n = stream.read(&mut buf).await.unwrap_or(0);
+                    let response = respond(port, &String::from_utf8_lossy(&buf[..n]));
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        port
+    }
+
+    fn http_response(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\n{headers}content-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    /// A feed and a site whose home page links to a web app manifest.
+    fn site_with_manifest(port: u16, request: &str) -> String {
+        if request.starts_with("GET /feed.xml ") {
+            http_response(
+                "200 OK",
+                "content-type: application/rss+xml\r\n",
+                &format!(
+                    "<rss version=\"2.0\"><channel><title>Limited</title><link>http://127.0.0.1:{port}/</link></channel></rss>"
+                ),
+            )
+        } else if request.starts_with("GET / ") {
+            http_response(
+                "200 OK",
+                "content-type: text/html\r\n",
+                r#"<link rel="manifest" href="/manifest.json">"#,
+            )
+        } else if request.starts_with("GET /manifest.json ") {
+            http_response(
+                "200 OK",
+                "content-type: application/manifest+json\r\n",
+                r#"{"icons": [{"src": "/icon.png"}]}"#,
+            )
+        } else {
+            http_response("404 Not Found", "", "")
+        }
+    }
+
+    #[tokio::test]
+    async fn releases_host_slot_once_body_is_read() {
+        set_host_limits(HostLimits {
+            max_concurrent: 1,
+            min_delay: Duration::ZERO,
+        });
+        let port = serve(site_with_manifest).await;
+
+        // the icon lookup fetches the home page, then its manifest and icons
+        // from the same host
+        let result = tokio::time::timeout(
+            Duration::from_secs(10),
+            load_feed(
+                &format!("http://127.0.0.1:{port}/feed.xml"),
+                None,
+                None,
+                LoadOptions::default(),
+            ),
+        )
+        .await
+        .expect("waiting for the host's only request slot");
+
+        let Ok(FeedResult::Loaded(loaded)) = result else {
+            panic!("feed not loaded: {result:?}");
+        };
+        assert_eq!(loaded.feed.title, "Limited");
+    }
+
+    /// A feed that moved for good and hasn't changed since.
+    fn moved_unchanged_feed(_port: u16, request: &str) -> String {
+        if request.starts_with("GET /old.xml ") {
+            http_response("301 Moved Permanently", "location: /new.xml\r\n", "")
+        } else if request.starts_with("GET /new.xml ")
+            && request
+                .to_ascii_lowercase()
+                .contains("if-none-match: \"v1\"")
+        {
+            http_response("304 Not Modified", "", "")
+        } else {
+            http_response("404 Not Found", "", "")
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_move_of_unmodified_feed() {
+        let port = serve(moved_unchanged_feed).await;
+
+        let result = load_feed(
+            &format!("http://127.0.0.1:{port}/old.xml"),
+            Some("\"v1\"".to_string()),
+            None,
+            LoadOptions::default(),
+        )
+        .await;
+
+        let Ok(FeedResult::NotModified { moved_to }) = result else {
+            panic!("feed not reported unmodified: {result:?}");
+        };
+        assert_eq!(moved_to, Some(format!("http://127.0.0.1:{port}/new.xml")));
+    }
+
+    /// A page that looks like a Mastodon profile but isn't one.
+    fn profile_like_page(_port: u16, request: &str) -> String {
+        if request.starts_with("GET /@writer ") {
+            http_response("200 OK", "content-type: text/html\r\n", "<h1>Writer</h1>")
+        } else if request.starts_with("GET /feed ") {
+            http_response(
+                "200 OK",
+                "content-type: application/rss+xml\r\n",
+                "<rss version=\"2.0\"><channel><title>Writer</title></channel></rss>",
+            )
+        } else {
+            http_response("404 Not Found", "", "")
+        }
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_discovery_when_site_pattern_misses() {
+        let port = serve(profile_like_page).await;
+
+        let result = load_feed(
+            &format!("http://127.0.0.1:{port}/@writer"),
+            None,
+            None,
+            LoadOptions {
+                load_icon: false,
+                ..LoadOptions::default()
+            },
+        )
+        .await;
+
+        let Ok(FeedResult::Loaded(loaded)) = result else {
+            panic!("feed not loaded: {result:?}");
+        };
+        assert_eq!(
+            loaded.feed.feed_url,
+            format!("http://127.0.0.1:{port}/feed")
+        );
+    }
+
+    /// Serves a redirect from `/image` to `target` and a PNG at `/secret`.
+    async fn redirecting_server(target: impl Fn(u16) -> String + Send + 'static) -> u16 {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0; 1024];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let response = if buf[..n].starts_with(b"GET /secret") {
+                    "HTTP/1.1 200 OK\r\ncontent-type: image/png\r\ncontent-length: 4\r\n\r\nsecr"
+                        .to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 302 Found\r\nlocation: {}\r\ncontent-length: 0\r\n\r\n",
+                        target(port)
+                    )
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn resolves_only_public_addresses() {
+        let resolve = |name: &str| PublicResolver.resolve(name.parse().unwrap());
+
+        assert!(resolve("localhost").await.is_err());
+        assert!(resolve("10.0.0.5").await.is_err());
+        let addrs: Vec<_> = resolve("93.184.215.14").await.unwrap().collect();
+        assert_eq!(addrs, vec![SocketAddr::from(([93, 184, 215, 14], 0))]);
+    }
+
+    #[tokio::test]
+    async fn refuses_image_redirects_to_local_addresses() {
+        let port = redirecting_server(|port| format!("http://127.0.0.1:{port}/secret")).await;
+        // a public name that resolves to the test server
+        let client = Client::builder()
+            .redirect(image_redirect_policy())
+            .resolve("images.example.com", ([127, 0, 0, 1], port).into())
+            .build()
+            .unwrap();
+
+        let result =
+            fetch_image_with(&client, &format!("http://images.example.com:{port}/image")).await;
+        assert!(matches!(result, Err(FetchError::Network(_))), "{result:?}");
+
+        // while public redirects are still followed
+        let port =
+            redirecting_server(|port| format!("http://images.example.com:{port}/secret")).await;
+        let client = Client::builder()
+            .redirect(image_redirect_policy())
+            .resolve("images.example.com", ([127, 0, 0, 1], port).into())
+            .build()
+            .unwrap();
+        let image = fetch_image_with(&client, &format!("http://images.example.com:{port}/image"))
+            .await
+            .unwrap();
+        assert_eq!(image.data, b"secr");
+    }
+}
@@ -0,0 +1,435 @@
+use quick_xml::{
+    NsReader, Reader,
+    escape::resolve_predefined_entity,
+    events::{BytesStart, Event},
+    name::ResolveResult,
+};
+use tracing::warn;
+
+use crate::{
+    db::NewEntry,
+    feed_loader::{
+        date::parse_first,
+        feed::{FeedFormat, ParsedFeed, unique},
+        sanitize::sanitize_entry,
+        schedule::syndication_interval,
+        title::with_fallback_title,
+        xml_base::{XmlBases, resolve},
+    },
+};
+
+const RSS_10_NS: &[u8] = b"http://purl.org/rss/1.0/";
+const RSS_090_NS: &[u8] = b"http://my.netscape.com/rdf/simple/0.9/";
+const DC_NS: &[u8] = b"http://purl.org/dc/elements/1.1/";
+const CONTENT_NS: &[u8] = b"http://purl.org/rss/1.0/modules/content/";
+const SY_NS: &[u8] = b"http://purl.org/rss/1.0/modules/syndication/";
+
+/// Whether the document's root element is `RDF`, regardless of the prefix used for it.
+pub fn is_rdf(bytes: &[u8]) -> bool {
+    let mut reader = Reader::from_reader(bytes);
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) => return element.local_name().as_ref() == b"RDF",
+            Ok(Event::Eof) | Err(_) => return false,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Parses an RSS 1.0 (or RSS 0.9) `rdf:RDF` document. Unlike RSS 2.0, items are
+/// siblings of `channel` and dates and authors come from the Dublin Core module.
+pub fn parse_rdf(
+    bytes: &[u8],
+    bases: &XmlBases,
+) -> anyhow::Result<(ParsedFeed, Vec<NewEntry>, usize)> {
+    let mut reader = NsReader::from_reader(bytes);
+    let mut buf = Vec::new();
+
+    let mut channel = RdfChannel::default();
+    let mut items = Vec::new();
+    let mut item: Option<RdfItem> = None;
+    let mut in_channel = false;
+    let mut in_ignored = false;
+
+    loop {
+        let (ns, event) = reader.read_resolved_event_into(&mut buf)?;
+        let ns = match ns {
+            ResolveResult::Bound(ns) => Some(ns.as_ref().to_vec()),
+            _ => None,
+        };
+
+        let step = match event {
+            Event::Start(element) => Step::Start(
+                RdfElement::from_name(ns.as_deref(), element.local_name().as_ref()),
+                about(&element),
+            ),
+            Event::End(element) => Step::End(RdfElement::from_name(
+                ns.as_deref(),
+                element.local_name().as_ref(),
+            )),
+            Event::Eof => break,
+            _ => Step::Other,
+        };
+        buf.clear();
+
+        match step {
+            Step::Start(RdfElement::Channel, _) => in_channel = true,
+            Step::End(RdfElement::Channel) => in_channel = false,
+            Step::Start(RdfElement::Ignored, _) => in_ignored = true,
+            Step::End(RdfElement::Ignored) => in_ignored = false,
+            Step::Start(RdfElement::Item, about) => {
+                item = Some(RdfItem {
+                    about,
+                    ..Default::default()
+                })
+            }
+            Step::End(RdfElement::Item) => items.extend(item.take()),
+            Step::Start(field, _) if field.is_text() => {
+                let text = read_text(&mut reader, &mut buf)?;
+
+                if text.is_empty() {
+                    continue;
+                }
+                if in_ignored {
+                    if field == RdfElement::Url {
+                        channel.image_url = Some(text);
+                    }
+                    continue;
+                }
+
+                if let Some(item) = item.as_mut() {
+                    item.set(field, text);
+                } else if in_channel {
+                    channel.set(field, text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    let (entries, skipped) = items.into_iter().enumerate().fold(
+        (Vec::new(), 0usize),
+        |(mut entries, mut skipped), (index, item)| {
+            let Some(url) = item.link.or(item.about.clone()) else {
+                warn!("no link found for item {:?}, skipping...", item.title);
+                skipped += 1;
+                return (entries, skipped);
+            };
+
+            let base = bases.item(index);
+
+            let entry = with_fallback_title(NewEntry {
+                title: item.title.unwrap_or_default(),
+                url: resolve(base, &url),
+                comments_url: None,
+                published_at: parse_first(item.date.as_deref(), &mut diagnostics),
+                entry_updated_at: None,
+                summary_type: item.description.as_ref().map(|_| "html".to_string()),
+                summary: item.description,
+                content_type: item.content.as_ref().map(|_| "html".to_string()),
+                content: item.content,
+                authors: unique(item.creators.iter().map(String::as_str)),
+                categories: unique(item.subjects.iter().map(String::as_str)),
+                enclosures: Vec::new(),
+                guid: item.about.map(|about| resolve(base, &about)),
+            });
+            entries.push(sanitize_entry(base, entry));
+
+            (entries, skipped)
+        },
+    );
+
+    Ok((
+        ParsedFeed {
+            title: channel.title.unwrap_or_default(),
+            format: FeedFormat::Rdf,
+            site_url: channel.link.map(|link| resolve(bases.feed(), &link)),
+            image_url: channel.image_url.map(|url| resolve(bases.feed(), &url)),
+            update_interval: channel.update_period.as_deref().and_then(|period| {
+                syndication_interval(period, channel.update_frequency.as_deref())
+            }),
+            diagnostics,
+        },
+        entries,
+        skipped,
+    ))
+}
+
+enum Step {
+    Start(RdfElement, Option<String>),
+    End(RdfElement),
+    Other,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum RdfElement {
+    Channel,
+    Item,
+    /// `image` and `textinput` have their own `title` and `link`.
+    Ignored,
+    Title,
+    Link,
+    /// The `url` of an `image`.
+    Url,
+    Description,
+    Date,
+    Creator,
+    Subject,
+    Encoded,
+    UpdatePeriod,
+    UpdateFrequency,
+    Other,
+}
+
+impl RdfElement {
+    fn from_name(ns: Option<&[u8]>, local_name: &[u8]) -> Self {
+        // Some feeds forget to declare the default namespace, so unbound
+        // elements are treated as RSS elements.
+        let is_rss = matches!(ns, None | Some(RSS_10_NS) | Some(RSS_090_NS));
+
+        match (local_name, ns) {
+            (b"channel", _) if is_rss => Self::Channel,
+            (b"item", _) if is_rss => Self::Item,
+            (b"image" | b"textinput", _) if is_rss => Self::Ignored,
+            (b"title", _) if is_rss => Self::Title,
+            (b"link", _) if is_rss => Self::Link,
+            (b"url", _) if is_rss => Self::Url,
+            (b"description", _) if is_rss => Self::Description,
+            (b"date", Some(DC_NS)) => Self::Date,
+            (b"creator", Some(DC_NS)) => Self::Creator,
+            (b"subject", Some(DC_NS)) => Self::Subject,
+            (b"encoded", Some(CONTENT_NS)) => Self::Encoded,
+            (b"updatePeriod", Some(SY_NS)) => Self::UpdatePeriod,
+            (b"updateFrequency", Some(SY_NS)) => Self::UpdateFrequency,
+            _ => Self::Other,
+        }
+    }
+
+    fn is_text(self) -> bool {
+        matches!(
+            self,
+            Self::Title
+                | Self::Link
+                | Self::Url
+                | Self::Description
+                | Self::Date
+                | Self::Creator
+                | Self::Subject
+                | Self::Encoded
+                | Self::UpdatePeriod
+                | Self::UpdateFrequency
+        )
+    }
+}
+
+#[derive(Default)]
+struct RdfChannel {
+    title: Option<String>,
+    link: Option<String>,
+    image_url: Option<String>,
+    update_period: Option<String>,
+    update_frequency: Option<String>,
+}
+
+impl RdfChannel {
+    fn set(&mut self, field: RdfElement, text: String) {
+        match field {
+            RdfElement::Title => self.title = Some(text),
+            RdfElement::Link => self.link = Some(text),
+            RdfElement::UpdatePeriod => self.update_period = Some(text),
+            RdfElement::UpdateFrequency => self.update_frequency = Some(text),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Default)]
+struct RdfItem {
+    about: Option<String>,
+    title: Option<String>,
+    link: Option<String>,
+    description: Option<String>,
+    content: Option<String>,
+    date: Option<String>,
+    creators: Vec<String>,
+    subjects: Vec<String>,
+}
+
+impl RdfItem {
+    fn set(&mut self, field: RdfElement, text: String) {
+        match field {
+            RdfElement::Title => self.title = Some(text),
+            RdfElement::Link => self.link = Some(text),
+            RdfElement::Description => self.description = Some(text),
+            RdfElement::Encoded => self.content = Some(text),
+            RdfElement::Date => self.date = Some(text),
+            RdfElement::Creator => self.creators.push(text),
+            RdfElement::Subject => self.subjects.push(text),
+            _ => {}
+        }
+    }
+}
+
+fn about(element: &BytesStart) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == b"about")
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty())
+}
+
+/// Reads the text content of the element whose start tag was just read,
+/// including text of any nested elements, up to its end tag.
+fn read_text(reader: &mut NsReader<&[u8]>, buf: &mut Vec<u8>) -> anyhow::Result<String> {
+    let mut text = String::new();
+    let mut depth = 0usize;
+
+    loop {
+        match reader.read_event_into(buf)? {
+            Event::Start(_) => depth += 1,
+            Event::End(_) if depth == 0 => break,
+            Event::End(_) => depth -= 1,
+            Event::Text(value) => text.push_str(&value.decode()?),
+            Event::CData(value) => text.push_str(&value.decode()?),
+            Event::GeneralRef(reference) => match reference.resolve_char_ref()? {
+                Some(ch) => text.push(ch),
+                None => {
+                    let name = reference.decode()?;
+                    match resolve_predefined_entity(&name) {
+                        Some(value) => text.push_str(value),
+                        None => {
+                            text.push('&');
+                            text.push_str(&name);
+                            text.push(';');
+                        }
+                    }
+                }
+            },
+            Event::Eof => anyhow::bail!("unexpected end of document"),
+            _ => {}
+        }
+        buf.clear();
+    }
+    buf.clear();
+
+    Ok(text.trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use url::Url;
+
+    use super::*;
+    use crate::feed_loader::feed::parse_feed;
+
+    const RSS_10: &[u8] = include_bytes!("fixtures/rss_10.rdf");
+    const RSS_090: &[u8] = include_bytes!("fixtures/rss_090.rdf");
+
+    fn bases(bytes: &[u8]) -> XmlBases {
+        XmlBases::read(bytes, &Url::parse("https://feeds.example.com/rdf").unwrap())
+    }
+
+    #[test]
+    fn detects_rdf_root() {
+        assert!(is_rdf(RSS_10));
+        assert!(is_rdf(RSS_090));
+        assert!(!is_rdf(
+            b"<?xml version=\"1.0\"?><rss version=\"2.0\"></rss>"
+        ));
+        assert!(!is_rdf(b"not xml at all"));
+    }
+
+    #[test]
+    fn parses_rss_10() {
+        let (feed, entries, skipped) = parse_rdf(RSS_10, &bases(RSS_10)).unwrap();
+
+        assert_eq!(feed.title, "Journal of Examples");
+        assert_eq!(
+            feed.site_url.as_deref(),
+            Some("https://journal.example.org/")
+        );
+        assert_eq!(entries.len(), 3);
+        assert_eq!(skipped, 0);
+
+        let first = &entries[0];
+        assert_eq!(first.title, "On the Nature of Examples & Counterexamples");
+        assert_eq!(first.url, "https://journal.example.org/articles/1");
+        assert_eq!(
+            first.published_at,
+            Some(Utc.with_ymd_and_hms(2026, 1, 5, 9, 30, 0).unwrap())
+        );
+        assert_eq!(first.authors, vec!["A. Author", "B. Author"]);
+        assert_eq!(first.categories, vec!["Philosophy"]);
+        assert_eq!(
+            first.summary.as_deref(),
+            Some("An abstract <em>about</em> things")
+        );
+        assert_eq!(first.summary_type.as_deref(), Some("html"));
+        assert_eq!(first.content.as_deref(), Some("<p>Full text</p>"));
+
+        // no <link>, falls back to rdf:about
+        let second = &entries[1];
+        assert_eq!(second.url, "https://journal.example.org/articles/2");
+        assert_eq!(second.guid.as_deref(), Some(second.url.as_str()));
+        assert!(second.authors.is_empty());
+        assert!(second.published_at.is_none());
+
+        // untitled, and nothing to make a title from
+        assert_eq!(entries[2].title, "Untitled");
+    }
+
+    #[test]
+    fn reads_syndication_module() {
+        let (feed, _, _) = parse_rdf(RSS_10, &bases(RSS_10)).unwrap();
+        assert_eq!(
+            feed.update_interval,
+            Some(std::time::Duration::from_secs(6 * 60 * 60))
+        );
+    }
+
+    #[test]
+    fn ignores_image_and_textinput_fields() {
+        let (feed, _, _) = parse_rdf(RSS_10, &bases(RSS_10)).unwrap();
+        assert_ne!(feed.title, "Journal logo");
+        assert_ne!(
+            feed.site_url.as_deref(),
+            Some("https://journal.example.org/search")
+        );
+        assert_eq!(
+            feed.image_url.as_deref(),
+            Some("https://journal.example.org/logo.png")
+        );
+    }
+
+    #[test]
+    fn parses_rss_090_with_custom_prefix() {
+        let (feed, entries, _) = parse_rdf(RSS_090, &bases(RSS_090)).unwrap();
+
+        assert_eq!(feed.title, "Old Style News");
+        assert_eq!(feed.site_url.as_deref(), Some("http://news.example.net/"));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "First headline");
+        assert_eq!(entries[0].url, "http://news.example.net/1");
+    }
+
+    #[test]
+    fn parse_feed_uses_rdf_parser() {
+        let (feed, entries) = parse_feed(
+            RSS_10,
+            &Url::parse("https://journal.example.org/rss").unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(feed.title, "Journal of Examples");
+        assert_eq!(feed.format, FeedFormat::Rdf);
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].published_at.is_some());
+    }
+}
@@ -0,0 +1,194 @@
+use url::Url;
+
+/// The feed of a page on a site with a known feed URL pattern, e.g. the
+/// releases feed of a GitHub repository, which isn't advertised in the page.
+pub fn site_feed_url(page_url: &Url) -> Option<String> {
+    let host = page_url.host_str()?.trim_start_matches("www.");
+    let segments: Vec<&str> = page_url
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+
+    match host {
+        "youtube.com" | "m.youtube.com" => youtube(page_url, &segments),
+        "github.com" => github(&segments),
+        "reddit.com" | "old.reddit.com" | "new.reddit.com" => reddit(&segments),
+        "medium.com" => medium(&segments),
+        _ if host.ends_with(".medium.com") => Some(format!("https://{host}/feed")),
+        _ if host.ends_with(".substack.com") => Some(format!("https://{host}/feed")),
+        _ => mastodon(page_url, &segments),
+    }
+}
+
+fn youtube(page_url: &Url, segments: &[&str]) -> Option<String> {
+    const FEEDS: &str = "https://www.youtube.com/feeds/videos.xml";
+
+    match segments {
+        ["channel", id, ..] => Some(format!("{FEEDS}?channel_id={id}")),
+        ["user", name, ..] => Some(format!("{FEEDS}?user={name}")),
+        ["playlist"] => page_url
+            .query_pairs()
+            .find(|(key, _)| key == "list")
+            .map(|(_, list)| format!("{FEEDS}?playlist_id={list}")),
+        // @handles and /c/ names need the channel id, which the page's own
+        // <link rel="alternate"> provides
+        _ => None,
+    }
+}
+
+fn github(segments: &[&str]) -> Option<String> {
+    const BASE: &str = "https://github.com";
+
+    match segments {
+        [user] if !GITHUB_RESERVED.contains(user) => Some(format!("{BASE}/{user}.atom")),
+        [owner, repo] | [owner, repo, "releases", ..] => {
+            Some(format!("{BASE}/{owner}/{repo}/releases.atom"))
+        }
+        [owner, repo, "tags", ..] => Some(format!("{BASE}/{owner}/{repo}/tags.atom")),
+        [owner, repo, "commits"] => Some(format!("{BASE}/{owner}/{repo}/commits.atom")),
+        [owner, repo, "commits", branch @ ..] => Some(format!(
+            "{BASE}/{owner}/{repo}/commits/{}.atom",
+            branch.join("/")
+        )),
+        _ => None,
+    }
+}
+
+/// Top-level GitHub paths that aren't users.
+const GITHUB_RESERVED: &[&str] = &[
+    "about",
+    "explore",
+    "features",
+    "login",
+    "marketplace",
+    "notifications",
+    "orgs",
+    "pricing",
+    "pulls",
+    "search",
+    "settings",
+    "sponsors",
+    "topics",
+    "trending",
+];
+
+fn reddit(segments: &[&str]) -> Option<String> {
+    match segments {
+        ["r", subreddit, ..] => Some(format!("https://www.reddit.com/r/{subreddit}/.rss")),
+        ["user" | "u", name, ..] => Some(format!("https://www.reddit.com/user/{name}/.rss")),
+        _ => None,
+    }
+}
+
+fn medium(segments: &[&str]) -> Option<String> {
+    match segments {
+        ["feed", ..] => None,
+        [publication, ..] => Some(format!("https://medium.com/feed/{publication}")),
+        [] => None,
+    }
+}
+
+/// Mastodon (and compatible) profiles live at `/@user` and have their feed
+/// at `/@user.rss`.
+fn mastodon(page_url: &Url, segments: &[&str]) -> Option<String> {
+    match segments {
+        [profile] if profile.len() > 1 && profile.starts_with('@') && !profile.contains('.') => {
+            let mut feed_url = page_url.clone();
+            feed_url.set_path(&format!("/{profile}.rss"));
+            feed_url.set_query(None);
+            feed_url.set_fragment(None);
+            Some(feed_url.to_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed_url(page_url: &str) -> Option<String> {
+        site_feed_url(&Url::parse(page_url).unwrap())
+    }
+
+    #[test]
+    fn rewrites_youtube() {
+        assert_eq!(
+            feed_url("https://www.youtube.com/channel/UC123/videos").as_deref(),
+            Some("https://www.youtube.com/feeds/videos.xml?channel_id=UC123")
+        );
+        assert_eq!(
+            feed_url("https://youtube.com/playlist?list=PL42").as_deref(),
+            Some("https://www.youtube.com/feeds/videos.xml?playlist_id=PL42")
+        );
+        assert_eq!(
+            feed_url("https://www.youtube.com/user/someone").as_deref(),
+            Some("https://www.youtube.com/feeds/videos.xml?user=someone")
+        );
+        assert_eq!(feed_url("https://www.youtube.com/@handle"), None);
+    }
+
+    #[test]
+    fn rewrites_github() {
+        assert_eq!(
+            feed_url("https://github.com/rust-lang/rust").as_deref(),
+            Some("https://github.com/rust-lang/rust/releases.atom")
+        );
+        assert_eq!(
+            feed_url("https://github.com/rust-lang/rust/releases/tag/1.0.0").as_deref(),
+            Some("https://github.com/rust-lang/rust/releases.atom")
+        );
+        assert_eq!(
+            feed_url("https://github.com/rust-lang/rust/commits/master").as_deref(),
+            Some("https://github.com/rust-lang/rust/commits/master.atom")
+        );
+        assert_eq!(
+            feed_url("https://github.com/octocat").as_deref(),
+            Some("https://github.com/octocat.atom")
+        );
+        assert_eq!(feed_url("https://github.com/rust-lang/rust/issues/1"), None);
+        assert_eq!(feed_url("https://github.com/explore"), None);
+    }
+
+    #[test]
+    fn rewrites_reddit() {
+        assert_eq!(
+            feed_url("https://old.reddit.com/r/rust/").as_deref(),
+            Some("https://www.reddit.com/r/rust/.rss")
+        );
+        assert_eq!(
+            feed_url("https://www.reddit.com/u/spez").as_deref(),
+            Some("https://www.reddit.com/user/spez/.rss")
+        );
+    }
+
+    #[test]
+    fn rewrites_medium_and_substack() {
+        assert_eq!(
+            feed_url("https://medium.com/@someone").as_deref(),
+            Some("https://medium.com/feed/@someone")
+        );
+        assert_eq!(
+            feed_url("https://medium.com/some-publication/a-post-123").as_deref(),
+            Some("https://medium.com/feed/some-publication")
+        );
+        assert_eq!(
+            feed_url("https://someone.medium.com/a-post").as_deref(),
+            Some("https://someone.medium.com/feed")
+        );
+        assert_eq!(
+            feed_url("https://newsletter.substack.com/p/a-post").as_deref(),
+            Some("https://newsletter.substack.com/feed")
+        );
+    }
+
+    #[test]
+    fn rewrites_mastodon_profiles() {
+        assert_eq!(
+            feed_url("https://mastodon.social/@Gargron").as_deref(),
+            Some("https://mastodon.social/@Gargron.rss")
+        );
+        assert_eq!(feed_url("https://mastodon.social/@Gargron/123"), None);
+        assert_eq!(feed_url("https://example.com/blog"), None);
+    }
+}
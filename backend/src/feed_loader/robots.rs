@@ -0,0 +1,250 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use reqwest::{Response, StatusCode, header::HeaderMap};
+use texting_robots::Robot;
+use url::Url;
+
+use crate::feed_loader::{
+    CLIENT, FetchError, freshness::freshness_lifetime, host_limit, user_agent,
+};
+
+/// RFC 9309 section 2.4: cached rules shouldn't be used for longer than this.
+const MAX_ROBOTS_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Keeps `no-cache` and tiny `max-age`s from fetching robots.txt before each
+/// of the few requests a feed load makes.
+const MIN_ROBOTS_TTL: Duration = Duration::from_secs(60);
+
+/// How long to wait before asking an unreachable robots.txt again.
+const UNREACHABLE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// RFC 9309 section 2.5 asks to parse at least this much; the rest is ignored.
+const MAX_ROBOTS_BYTES: usize = 500 * 1024;
+
+/// robots.txt rules by origin, shared by every feed load in the process.
+static ROBOTS: Lazy<Mutex<HashMap<String, CachedRobots>>> = Lazy::new(Mutex::default);
+
+struct CachedRobots {
+    rules: Rules,
+    expires_at: Instant,
+}
+
+enum Rules {
+    /// There's no robots.txt, or it's unavailable (4xx): anything goes.
+    AllowAll,
+    Robot(Robot),
+    /// The server failed (5xx) or couldn't be reached, which RFC 9309 says
+    /// means nothing may be fetched.
+    Unreachable,
+}
+
+impl Rules {
+    fn allows(&self, url: &Url) -> Result<bool, FetchError> {
+        match self {
+            Rules::AllowAll => Ok(true),
+            Rules::Robot(robot) => Ok(robot.allowed(url.as_str())),
+            Rules::Unreachable => Err(FetchError::RobotsFetchFailed),
+        }
+    }
+}
+
+/// Whether robots.txt lets us fetch `url`. Rules are cached for as long as
+/// the robots.txt response allows, up to a day; when it can't be fetched
+/// again, expired rules are kept rather than blocking the site.
+pub(super) async fn is_allowed(url: &Url) -> Result<bool, FetchError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(FetchError::InvalidUrl);
+    }
+    let origin = url.origin().ascii_serialization();
+
+    let stale_allowed = {
+        let robots = ROBOTS.lock().expect("robots cache lock poisoned");
+        match robots.get(&origin) {
+            Some(cached) if cached.expires_at > Instant::now() => {
+                let allowed = cached.rules.allows(url);
+                tracing::trace!(%url, ?allowed, "robots.txt check (cached)");
+                return allowed;
+            }
+            Some(cached) => cached.rules.allows(url).ok(),
+            None => None,
+        }
+    };
+
+    let (rules, ttl) = fetch_robots(&origin).await;
+    let allowed = match (&rules, stale_allowed) {
+        (Rules::Unreachable, Some(stale_allowed)) => {
+            tracing::debug!(origin, "robots.txt unreachable, using expired rules");
+            Ok(stale_allowed)
+        }
+        _ => rules.allows(url),
+    };
+    tracing::trace!(%url, ?allowed, ?ttl, "robots.txt check");
+
+    let mut robots = ROBOTS.lock().expect("robots cache lock poisoned");
+    let now = Instant::now();
+    match robots.get_mut(&origin) {
+        // the expired rules that were just used are kept until it's back
+        Some(cached) if matches!(rules, Rules::Unreachable) && stale_allowed.is_some() => {
+            cached.expires_at = now + ttl;
+        }
+        _ => {
+            robots.insert(
+                origin,
+                CachedRobots {
+                    rules,
+                    expires_at: now + ttl,
+                },
+            );
+        }
+    }
+    robots.retain(|_, cached| cached.expires_at > now);
+
+    allowed
+}
+
+async fn fetch_robots(origin: &str) -> (Rules, Duration) {
+    let Ok(robots_url) = Url::parse(&format!("{origin}/robots.txt")) else {
+        return (Rules::AllowAll, MAX_ROBOTS_TTL);
+    };
+    tracing::trace!("fetching robots.txt from: {robots_url}");
+
+    let request = CLIENT.get(robots_url.clone());
+    let response = match host_limit::send(&robots_url, request).await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::debug!("error fetching robots.txt: {e}");
+            return (Rules::Unreachable, UNREACHABLE_TTL);
+        }
+    };
+
+    let status = response.status();
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        tracing::debug!(%status, "robots.txt unreachable");
+        return (Rules::Unreachable, UNREACHABLE_TTL);
+    }
+    let ttl = robots_ttl(response.headers());
+    if !status.is_success() {
+        return (Rules::AllowAll, ttl);
+    }
+
+    let bytes = match read_prefix(response, MAX_ROBOTS_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::debug!("error reading robots.txt: {e}");
+            return (Rules::Unreachable, UNREACHABLE_TTL);
+        }
+    };
+
+    (parse_robots(&bytes), ttl)
+}
+
+/// Rules for our product token, e.g. `rss-reader` for `rss-reader/1.0`.
+/// Lines that can't be parsed are ignored, and a file that can't be parsed
+/// at all is treated like a missing one.
+fn parse_robots(bytes: &[u8]) -> Rules {
+    match Robot::new(robots_agent(user_agent()), bytes) {
+        Ok(robot) => Rules::Robot(robot),
+        Err(e) => {
+            tracing::debug!("error parsing robots.txt: {e:#}");
+            Rules::AllowAll
+        }
+    }
+}
+
+/// The product token robots.txt groups are matched against, which is the
+/// start of the user agent.
+fn robots_agent(user_agent: &str) -> &str {
+    let end = user_agent
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        .unwrap_or(user_agent.len());
+    &user_agent[..end]
+}
+
+/// How long the response allows caching it, bounded to keep rules neither
+/// too long nor refetched all the time, and a day when it doesn't say.
+fn robots_ttl(headers: &HeaderMap) -> Duration {
+    freshness_lifetime(headers)
+        .map(|lifetime| lifetime.clamp(MIN_ROBOTS_TTL, MAX_ROBOTS_TTL))
+        .unwrap_or(MAX_ROBOTS_TTL)
+}
+
+/// The first `max_bytes` of a response body.
+async fn read_prefix(mut response: Response, max_bytes: usize) -> Result<Vec<u8>, FetchError> {
+    let mut data = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        data.extend_from_slice(&chunk);
+        if data.len() >= max_bytes {
+            data.truncate(max_bytes);
+            break;
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{self, HeaderValue};
+
+    use super::*;
+
+    fn cache_control(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    fn allows(rules: &Rules, url: &str) -> bool {
+        rules.allows(&Url::parse(url).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn matches_product_token() {
+        assert_eq!(robots_agent("rss-reader"), "rss-reader");
+        assert_eq!(
+            robots_agent("rss-reader/1.0 (+https://example.com)"),
+            "rss-reader"
+        );
+        assert_eq!(robots_agent("Feed_Bot 2"), "Feed_Bot");
+    }
+
+    #[test]
+    fn applies_rules_for_our_agent() {
+        let rules = parse_robots(
+            b"User-agent: *\nDisallow: /\n\nUser-agent: rss-reader\nDisallow: /private\n",
+        );
+        assert!(allows(&rules, "https://example.com/feed.xml"));
+        assert!(!allows(&rules, "https://example.com/private/feed.xml"));
+
+        let rules = parse_robots(b"User-agent: *\nDisallow: /feeds/\n");
+        assert!(allows(&rules, "https://example.com/"));
+        assert!(!allows(&rules, "https://example.com/feeds/all.xml"));
+    }
+
+    #[test]
+    fn blocks_unreachable_sites() {
+        assert!(
+            Rules::Unreachable
+                .allows(&Url::parse("https://example.com/").unwrap())
+                .is_err()
+        );
+        assert!(allows(&Rules::AllowAll, "https://example.com/"));
+    }
+
+    #[test]
+    fn follows_cache_control() {
+        assert_eq!(robots_ttl(&HeaderMap::new()), MAX_ROBOTS_TTL);
+        assert_eq!(
+            robots_ttl(&cache_control("public, max-age=3600")),
+            Duration::from_secs(3600)
+        );
+        assert_eq!(robots_ttl(&cache_control("max-age=604800")), MAX_ROBOTS_TTL);
+        assert_eq!(robots_ttl(&cache_control("max-age=0")), MIN_ROBOTS_TTL);
+        assert_eq!(robots_ttl(&cache_control("no-store")), MIN_ROBOTS_TTL);
+        assert_eq!(robots_ttl(&cache_control("max-age=soon")), MAX_ROBOTS_TTL);
+    }
+}
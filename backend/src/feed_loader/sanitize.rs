@@ -0,0 +1,488 @@
+use html5ever::{ParseOpts, QualName, local_name, ns, parse_fragment, tendril::TendrilSink};
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+use url::Url;
+
+use crate::{db::NewEntry, feed_loader::xml_base::resolve};
+
+/// Sanitizes the HTML summary and content of an entry, see [`sanitize_html`].
+/// Plain text bodies are left alone.
+pub fn sanitize_entry(base: &Url, mut entry: NewEntry) -> NewEntry {
+    if is_html(entry.summary_type.as_deref()) {
+        entry.summary = entry.summary.map(|summary| sanitize_html(&summary, base));
+    }
+    if is_html(entry.content_type.as_deref()) {
+        entry.content = entry.content.map(|content| sanitize_html(&content, base));
+    }
+    entry
+}
+
+/// Whether an entry body of this type is HTML.
+pub fn is_html(body_type: Option<&str>) -> bool {
+    matches!(
+        body_type,
+        Some("html" | "xhtml" | "text/html" | "application/xhtml+xml")
+    )
+}
+
+/// Makes untrusted HTML from a feed safe to hand to clients: only allowlisted
+/// tags and attributes are kept, URLs are resolved against `base` and dropped
+/// unless they're http(s) (or `mailto:` links and `data:` images), iframes
+/// are kept only for known video hosts, links get `rel="noopener noreferrer"`
+/// and tracking pixels are removed. Unknown tags are unwrapped, keeping their
+/// text.
+pub fn sanitize_html(html: &str, base: &Url) -> String {
+    let dom = parse_fragment(
+        RcDom::default(),
+        ParseOpts::default(),
+        QualName::new(None, ns!(html), local_name!("body")),
+        Vec::new(),
+        false,
+    )
+    .one(html);
+
+    let mut sanitized = String::with_capacity(html.len());
+    // the fragment parser puts everything inside an <html> element
+    for root in dom.document.children.borrow().iter() {
+        write_children(root, base, &mut sanitized);
+    }
+    sanitized
+}
+
+fn write_node(node: &Handle, base: &Url, out: &mut String) {
+    match &node.data {
+        NodeData::Text { contents } => escape(&contents.borrow(), false, out),
+        NodeData::Element { name, attrs, .. } => {
+            let tag = name.local.as_ref();
+
+            if DROPPED_ELEMENTS.contains(&tag) {
+                return;
+            }
+            if !ALLOWED_ELEMENTS.contains(&tag) {
+                write_children(node, base, out);
+                return;
+            }
+
+            let attrs = attrs.borrow();
+            let mut kept: Vec<(&str, String)> = Vec::new();
+            for attr in attrs.iter() {
+                let name = attr.name.local.as_ref();
+                if !is_allowed_attr(tag, name) {
+                    continue;
+                }
+                let value = match name {
+                    "href" | "src" | "poster" | "cite" => {
+                        match safe_url(tag, name, &attr.value, base) {
+                            Some(url) => url,
+                            None => continue,
+                        }
+                    }
+                    "srcset" => match safe_srcset(&attr.value, base) {
+                        Some(srcset) => srcset,
+                        None => continue,
+                    },
+                    _ => attr.value.to_string(),
+                };
+                kept.push((name, value));
+            }
+
+            match tag {
+                "img" if is_tracking_pixel(&kept) => return,
+                "iframe" => {
+                    let embeddable = kept
+                        .iter()
+                        .any(|(name, value)| *name == "src" && is_embeddable(value));
+                    if !embeddable {
+                        return;
+                    }
+                    kept.push(("sandbox", IFRAME_SANDBOX.to_owned()));
+                }
+                "a" => kept.push(("rel", "noopener noreferrer".to_owned())),
+                _ => {}
+            }
+
+            out.push('<');
+            out.push_str(tag);
+            for (name, value) in &kept {
+                out.push(' ');
+                out.push_str(name);
+                out.push_str("=\"");
+                escape(value, true, out);
+                out.push('"');
+            }
+            out.push('>');
+
+            if VOID_ELEMENTS.contains(&tag) {
+                return;
+            }
+
+            write_children(node, base, out);
+            out.push_str("</");
+            out.push_str(tag);
+            out.push('>');
+        }
+        // comments, doctypes and processing instructions
+        _ => {}
+    }
+}
+
+fn write_children(node: &Handle, base: &Url, out: &mut String) {
+    for child in node.children.borrow().iter() {
+        write_node(child, base, out);
+    }
+}
+
+fn is_allowed_attr(tag: &str, attr: &str) -> bool {
+    GLOBAL_ATTRS.contains(&attr)
+        || ALLOWED_ATTRS
+            .iter()
+            .any(|(element, attrs)| *element == tag && attrs.contains(&attr))
+}
+
+/// Resolves the URL, keeping it only if its scheme can't run script.
+fn safe_url(tag: &str, attr: &str, value: &str, base: &Url) -> Option<String> {
+    let resolved = resolve(base, value);
+    let url = Url::parse(&resolved).ok()?;
+
+    let allowed = match url.scheme() {
+        "http" | "https" => true,
+        "mailto" => tag == "a" && attr == "href",
+        "data" => tag == "img" && attr == "src" && is_image_data_url(url.path()),
+        _ => false,
+    };
+    allowed.then_some(resolved)
+}
+
+fn is_image_data_url(path: &str) -> bool {
+    let media_type = path.split([';', ',']).next().unwrap_or_default();
+    media_type.starts_with("image/") && media_type != "image/svg+xml"
+}
+
+fn safe_srcset(value: &str, base: &Url) -> Option<String> {
+    let candidates: Vec<String> = value
+        .split(',')
+        .filter_map(|candidate| {
+            let mut parts = candidate.split_whitespace();
+            let url = safe_url("img", "srcset", parts.next()?, base)?;
+            Some(
+                std::iter::once(url)
+                    .chain(parts.map(str::to_owned))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            )
+        })
+        .collect();
+
+    (!candidates.is_empty()).then(|| candidates.join(", "))
+}
+
+/// 1x1 images, images served by known analytics hosts, and images without
+/// any usable source.
+fn is_tracking_pixel(attrs: &[(&str, String)]) -> bool {
+    let dimension = |name: &str| {
+        attrs
+            .iter()
+            .find(|(attr, _)| *attr == name)
+            .and_then(|(_, value)| value.trim().trim_end_matches("px").parse::<u32>().ok())
+    };
+    if matches!((dimension("width"), dimension("height")), (Some(w), Some(h)) if w <= 1 && h <= 1) {
+        return true;
+    }
+
+    let Some(src) = attrs
+        .iter()
+        .find(|(attr, _)| *attr == "src")
+        .and_then(|(_, src)| Url::parse(src).ok())
+    else {
+        return !attrs.iter().any(|(attr, _)| *attr == "srcset");
+    };
+
+    let host = src.host_str().unwrap_or_default();
+    TRACKING_HOSTS.contains(&host)
+        || TRACKING_PATHS
+            .iter()
+            .any(|(tracker_host, path)| host == *tracker_host && src.path().starts_with(path))
+}
+
+fn is_embeddable(src: &str) -> bool {
+    Url::parse(src).is_ok_and(|url| {
+        url.scheme() == "https"
+            && url
+                .host_str()
+                .is_some_and(|host| EMBED_HOSTS.contains(&host))
+    })
+}
+
+fn escape(text: &str, in_attribute: bool, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '\u{a0}' => out.push_str("&nbsp;"),
+            '"' if in_attribute => out.push_str("&quot;"),
+            '<' if !in_attribute => out.push_str("&lt;"),
+            '>' if !in_attribute => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Hosts whose players may be embedded.
+const EMBED_HOSTS: &[&str] = &[
+    "www.youtube.com",
+    "www.youtube-nocookie.com",
+    "player.vimeo.com",
+    "www.dailymotion.com",
+];
+
+const IFRAME_SANDBOX: &str = "allow-scripts allow-same-origin allow-presentation allow-popups";
+
+/// Removed along with everything inside them.
+const DROPPED_ELEMENTS: &[&str] = &[
+    "applet", "base", "button", "embed", "frame", "frameset", "head", "input", "link", "math",
+    "meta", "noscript", "object", "script", "select", "style", "svg", "template", "textarea",
+    "title",
+];
+
+const ALLOWED_ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "i",
+    "iframe",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "picture",
+    "pre",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "section",
+    "small",
+    "source",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "u",
+    "ul",
+    "var",
+    "video",
+    "wbr",
+];
+
+const VOID_ELEMENTS: &[&str] = &["br", "col", "hr", "img", "source", "wbr"];
+
+const GLOBAL_ATTRS: &[&str] = &["dir", "lang", "title"];
+
+const ALLOWED_ATTRS: &[(&str, &[&str])] = &[
+    ("a", &["href"]),
+    ("audio", &["controls", "src"]),
+    ("blockquote", &["cite"]),
+    ("col", &["span"]),
+    ("colgroup", &["span"]),
+    ("del", &["cite", "datetime"]),
+    ("details", &["open"]),
+    ("iframe", &["allowfullscreen", "height", "src", "width"]),
+    ("img", &["alt", "height", "src", "srcset", "width"]),
+    ("ins", &["cite", "datetime"]),
+    ("ol", &["reversed", "start", "type"]),
+    ("q", &["cite"]),
+    ("source", &["media", "src", "srcset", "type"]),
+    ("td", &["colspan", "headers", "rowspan"]),
+    ("th", &["colspan", "headers", "rowspan", "scope"]),
+    ("time", &["datetime"]),
+    ("video", &["controls", "height", "poster", "src", "width"]),
+];
+
+const TRACKING_HOSTS: &[&str] = &[
+    "pixel.wp.com",
+    "stats.wordpress.com",
+    "www.google-analytics.com",
+    "pixel.quantserve.com",
+    "feeds.feedblitz.com",
+    "ad.doubleclick.net",
+];
+
+const TRACKING_PATHS: &[(&str, &str)] = &[
+    ("feeds.feedburner.com", "/~r/"),
+    ("feeds.feedburner.com", "/~ff/"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sanitize(html: &str) -> String {
+        sanitize_html(
+            html,
+            &Url::parse("https://blog.example.com/posts/1").unwrap(),
+        )
+    }
+
+    #[test]
+    fn keeps_allowed_markup() {
+        assert_eq!(
+            sanitize("<p>Hello <b>bold</b> &amp; <em title=\"x\">em</em><br></p>"),
+            "<p>Hello <b>bold</b> &amp; <em title=\"x\">em</em><br></p>"
+        );
+    }
+
+    #[test]
+    fn drops_scripts_and_unknown_attributes() {
+        assert_eq!(
+            sanitize(
+                "<p onclick=\"steal()\" style=\"color:red\" class=\"x\">Hi</p><script>steal()</script><style>p{}</style>"
+            ),
+            "<p>Hi</p>"
+        );
+    }
+
+    #[test]
+    fn unwraps_unknown_elements() {
+        assert_eq!(
+            sanitize("<font color=red><center>Old</center></font><form><p>Inside</p></form>"),
+            "Old<p>Inside</p>"
+        );
+    }
+
+    #[test]
+    fn strips_script_urls() {
+        assert_eq!(
+            sanitize("<a href=\"javascript:alert(1)\">x</a><a href=\" JaVaScRiPt:alert(1)\">y</a>"),
+            "<a rel=\"noopener noreferrer\">x</a><a rel=\"noopener noreferrer\">y</a>"
+        );
+        assert_eq!(
+            sanitize("<img src=\"data:text/html;base64,PHNjcmlwdD4=\" width=\"10\" height=\"10\">"),
+            ""
+        );
+    }
+
+    #[test]
+    fn resolves_links_and_adds_rel() {
+        assert_eq!(
+            sanitize("<a href=\"../about\" rel=\"opener\" target=\"_self\">About</a>"),
+            "<a href=\"https://blog.example.com/about\" rel=\"noopener noreferrer\">About</a>"
+        );
+        assert_eq!(
+            sanitize("<a href=\"mailto:me@example.com\">Mail</a>"),
+            "<a href=\"mailto:me@example.com\" rel=\"noopener noreferrer\">Mail</a>"
+        );
+    }
+
+    #[test]
+    fn resolves_image_sources() {
+        assert_eq!(
+            sanitize(
+                "<img src=\"/a.png\" srcset=\"/a.png 1x, javascript:x 2x, b.png 3x\" alt=\"A\">"
+            ),
+            "<img src=\"https://blog.example.com/a.png\" srcset=\"https://blog.example.com/a.png 1x, https://blog.example.com/posts/b.png 3x\" alt=\"A\">"
+        );
+    }
+
+    #[test]
+    fn keeps_iframes_of_video_hosts_only() {
+        assert_eq!(
+            sanitize(
+                "<iframe src=\"https://www.youtube-nocookie.com/embed/abc\" width=\"560\" onload=\"x()\"></iframe>"
+            ),
+            format!(
+                "<iframe src=\"https://www.youtube-nocookie.com/embed/abc\" width=\"560\" sandbox=\"{IFRAME_SANDBOX}\"></iframe>"
+            )
+        );
+        assert_eq!(
+            sanitize("<iframe src=\"https://evil.example.com/\"></iframe><p>After</p>"),
+            "<p>After</p>"
+        );
+        assert_eq!(
+            sanitize("<iframe src=\"http://www.youtube.com/embed/abc\"></iframe>"),
+            ""
+        );
+    }
+
+    #[test]
+    fn removes_tracking_pixels() {
+        assert_eq!(
+            sanitize(
+                "<p>Text<img src=\"https://example.com/t.gif\" width=\"1\" height=\"1\"></p>\
+                 <img src=\"https://feeds.feedburner.com/~r/Blog/~4/abc\">\
+                 <img src=\"https://pixel.wp.com/g.gif?blog=1\">\
+                 <img src=\"https://example.com/photo.jpg\" width=\"600\" height=\"1\">"
+            ),
+            "<p>Text</p><img src=\"https://example.com/photo.jpg\" width=\"600\" height=\"1\">"
+        );
+    }
+
+    #[test]
+    fn escapes_text_and_attributes() {
+        assert_eq!(
+            sanitize("<p title='a \"quote\" &amp; <tag>'>1 &lt; 2</p>"),
+            "<p title=\"a &quot;quote&quot; &amp; <tag>\">1 &lt; 2</p>"
+        );
+    }
+
+    #[test]
+    fn leaves_text_bodies_alone() {
+        let base = Url::parse("https://blog.example.com/").unwrap();
+        let entry = sanitize_entry(
+            &base,
+            NewEntry {
+                summary: Some("<b>not html</b>".to_string()),
+                summary_type: Some("text".to_string()),
+                content: Some("<b>html</b><script>x</script>".to_string()),
+                content_type: Some("html".to_string()),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(entry.summary.as_deref(), Some("<b>not html</b>"));
+        assert_eq!(entry.content.as_deref(), Some("<b>html</b>"));
+    }
+}
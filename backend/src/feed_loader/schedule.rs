@@ -0,0 +1,195 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+/// Used until a feed's posting frequency is known.
+pub const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Longest wait between attempts at a failing feed.
+pub const MAX_BACKOFF_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How many of the newest entries the posting frequency is estimated from.
+const POSTING_SAMPLE: usize = 10;
+
+/// The shortest and longest time between syncs of a feed.
+#[derive(Debug, Clone, Copy)]
+pub struct SyncBounds {
+    pub min_interval: Duration,
+    pub max_interval: Duration,
+}
+
+impl Default for SyncBounds {
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_secs(15 * 60),
+            max_interval: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl SyncBounds {
+    pub fn clamp(&self, interval: Duration) -> Duration {
+        interval.clamp(self.min_interval, self.max_interval)
+    }
+}
+
+/// How long to wait before syncing a feed again: about as long as it takes
+/// the feed to post, but never shorter than what the publisher asks for with
+/// `<ttl>`, `sy:updatePeriod` or the response's freshness.
+pub fn sync_interval(
+    posting_interval: Option<Duration>,
+    publisher_hints: &[Option<Duration>],
+    bounds: &SyncBounds,
+) -> Duration {
+    let interval = publisher_hints.iter().flatten().fold(
+        posting_interval.unwrap_or(DEFAULT_SYNC_INTERVAL),
+        |a, &b| a.max(b),
+    );
+    bounds.clamp(interval)
+}
+
+/// The typical time between the newest entries, by the median gap between
+/// their publication dates. A feed that has gone quiet for longer than that
+/// is given the time since its last entry instead, so it slows down.
+pub fn posting_interval(
+    published: impl IntoIterator<Item = DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    let mut published: Vec<_> = published.into_iter().filter(|date| *date <= now).collect();
+    published.sort_unstable_by(|a, b| b.cmp(a));
+    published.dedup();
+    published.truncate(POSTING_SAMPLE);
+
+    let mut gaps: Vec<Duration> = published
+        .windows(2)
+        .filter_map(|pair| (pair[0] - pair[1]).to_std().ok())
+        .collect();
+    if gaps.is_empty() {
+        return None;
+    }
+    gaps.sort_unstable();
+    let median = gaps[gaps.len() / 2];
+
+    let quiet_for = (now - published[0]).to_std().unwrap_or(Duration::ZERO);
+    Some(median.max(quiet_for))
+}
+
+/// How long to wait after the `failures`th failure in a row: the usual
+/// interval, doubled for each failure after the first, and no shorter than
+/// the server's `Retry-After`.
+pub fn backoff_interval(
+    interval: Duration,
+    failures: u32,
+    retry_after: Option<Duration>,
+) -> Duration {
+    let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+    let backoff = interval
+        .saturating_mul(factor)
+        .min(MAX_BACKOFF_INTERVAL)
+        .max(interval);
+    match retry_after {
+        Some(retry_after) => backoff.max(retry_after.min(MAX_BACKOFF_INTERVAL)),
+        None => backoff,
+    }
+}
+
+/// RSS `<ttl>`, in minutes.
+pub fn ttl_interval(ttl: &str) -> Option<Duration> {
+    let minutes = ttl.trim().parse::<u64>().ok()?;
+    Some(Duration::from_secs(minutes.checked_mul(60)?))
+}
+
+/// `sy:updatePeriod` divided by `sy:updateFrequency`, which defaults to 1.
+/// https://web.resource.org/rss/1.0/modules/syndication/
+pub fn syndication_interval(period: &str, frequency: Option<&str>) -> Option<Duration> {
+    let period_secs: u64 = match period.trim().to_ascii_lowercase().as_str() {
+        "hourly" => 60 * 60,
+        "daily" => 24 * 60 * 60,
+        "weekly" => 7 * 24 * 60 * 60,
+        "monthly" => 30 * 24 * 60 * 60,
+        "yearly" => 365 * 24 * 60 * 60,
+        _ => return None,
+    };
+    let frequency = match frequency {
+        Some(frequency) => frequency.trim().parse::<u64>().ok().filter(|f| *f > 0)?,
+        None => 1,
+    };
+    Some(Duration::from_secs(period_secs / frequency))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    fn hours_ago(now: DateTime<Utc>, hours: &[i64]) -> Vec<DateTime<Utc>> {
+        hours
+            .iter()
+            .map(|hours| now - chrono::Duration::hours(*hours))
+            .collect()
+    }
+
+    #[test]
+    fn estimates_posting_interval() {
+        let now = Utc::now();
+
+        assert_eq!(
+            posting_interval(hours_ago(now, &[1, 3, 5, 7, 30]), now),
+            Some(2 * HOUR)
+        );
+        // quiet for longer than it used to post
+        assert_eq!(
+            posting_interval(hours_ago(now, &[48, 50, 52]), now),
+            Some(48 * HOUR)
+        );
+        assert_eq!(posting_interval(hours_ago(now, &[1]), now), None);
+        assert_eq!(posting_interval(Vec::new(), now), None);
+    }
+
+    #[test]
+    fn respects_publisher_hints_and_bounds() {
+        let bounds = SyncBounds::default();
+
+        assert_eq!(sync_interval(None, &[], &bounds), DEFAULT_SYNC_INTERVAL);
+        assert_eq!(sync_interval(Some(2 * HOUR), &[None], &bounds), 2 * HOUR);
+        assert_eq!(
+            sync_interval(Some(2 * HOUR), &[Some(3 * HOUR), Some(HOUR)], &bounds),
+            3 * HOUR
+        );
+        assert_eq!(
+            sync_interval(Some(Duration::from_secs(60)), &[], &bounds),
+            bounds.min_interval
+        );
+        assert_eq!(
+            sync_interval(Some(24 * 30 * HOUR), &[], &bounds),
+            bounds.max_interval
+        );
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        assert_eq!(backoff_interval(HOUR, 1, None), HOUR);
+        assert_eq!(backoff_interval(HOUR, 2, None), 2 * HOUR);
+        assert_eq!(backoff_interval(HOUR, 4, None), 8 * HOUR);
+        assert_eq!(backoff_interval(HOUR, 40, None), MAX_BACKOFF_INTERVAL);
+        // the usual interval may already be longer than the cap
+        assert_eq!(backoff_interval(30 * 24 * HOUR, 3, None), 30 * 24 * HOUR);
+        assert_eq!(backoff_interval(HOUR, 1, Some(3 * HOUR)), 3 * HOUR);
+        assert_eq!(backoff_interval(HOUR, 3, Some(HOUR / 2)), 4 * HOUR);
+        assert_eq!(
+            backoff_interval(HOUR, 1, Some(365 * 24 * HOUR)),
+            MAX_BACKOFF_INTERVAL
+        );
+    }
+
+    #[test]
+    fn reads_update_hints() {
+        assert_eq!(ttl_interval(" 60 "), Some(HOUR));
+        assert_eq!(ttl_interval("soon"), None);
+        assert_eq!(syndication_interval("hourly", None), Some(HOUR));
+        assert_eq!(syndication_interval("daily", Some("2")), Some(12 * HOUR));
+        assert_eq!(syndication_interval("Weekly", Some("0")), None);
+        assert_eq!(syndication_interval("fortnightly", None), None);
+    }
+}
@@ -1,68 +1,105 @@
 use std::time::Duration;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use futures::{StreamExt, stream};
 use tokio::sync::watch;
 
 use crate::{
-    db::Data,
+    db::{Data, FeedToSync},
     feed_loader::{
-        FeedResult, SYNC_RESULT_DB_ERROR, SYNC_RESULT_NOT_MODIFIED, load_feed,
-        sync_result_for_error, sync_result_for_feed_result,
+        DEFAULT_SYNC_INTERVAL, FeedError, FeedResult, LoadOptions, LoadedFeed,
+        SYNC_RESULT_DB_ERROR, SYNC_RESULT_NOT_MODIFIED, SyncBounds, interleave_hosts, load_feed,
+        load_full_content, schedule::backoff_interval, sync_result_for_error,
+        sync_result_for_feed_result,
     },
 };
 
 static MAX_SYNCING_FEEDS: usize = 10;
+/// Keeps the loop from spinning when a due feed is claimed elsewhere.
+static MIN_SYNC_LOOP_WAIT: Duration = Duration::from_secs(1);
+/// Bounds how long a sync of a feed with full content extraction takes; the
+/// rest is picked up by the next syncs.
+static MAX_EXTRACTIONS_PER_SYNC: i64 = 20;
+/// Turning on full content extraction doesn't go through a feed's whole
+/// archive, only what was published recently.
+static MAX_EXTRACTION_AGE: chrono::Duration = chrono::Duration::days(7);
+static MAX_CONCURRENT_EXTRACTIONS: usize = 4;
+/// Icons rarely change, and looking for one costs a few requests to the site.
+static ICON_REFRESH_INTERVAL: chrono::Duration = chrono::Duration::days(7);
+/// Syncs in a row a feed has to redirect permanently to the same place before
+/// its URL is changed, so a passing misconfiguration doesn't move it.
+static REDIRECTS_BEFORE_MOVE: i32 = 3;
 
+/// Whether a feed's icon is due to be looked for again.
+pub fn icon_needs_refresh(icon_refreshed_at: Option<DateTime<Utc>>) -> bool {
+    icon_refreshed_at.is_none_or(|refreshed_at| Utc::now() - refreshed_at > ICON_REFRESH_INTERVAL)
+}
+
+/// Sleeps until the next feed is due. Feeds scheduled while it sleeps are
+/// picked up after at most the minimum interval.
 pub async fn feed_sync_loop(
     data: Data,
+    bounds: SyncBounds,
     mut shutdown_rx: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
-    let mut ticker = tokio::time::interval(Duration::from_hours(1));
-
     loop {
+        let wait = match data.get_next_sync_at().await? {
+            Some(next_sync_at) => (next_sync_at - Utc::now())
+                .to_std()
+                .unwrap_or(Duration::ZERO)
+                .clamp(MIN_SYNC_LOOP_WAIT, bounds.min_interval),
+            None => bounds.min_interval,
+        };
+        tracing::debug!(?wait, "waiting for the next feed to sync");
+
         tokio::select! {
-            _ = ticker.tick() => {}
+            _ = tokio::time::sleep(wait) => {}
             _ = shutdown_rx.wait_for(|&v| v) => {
                 tracing::info!("feed sync loop shutting down");
                 return Ok(());
             }
         }
 
-        let feeds = data
-            .get_feeds_to_sync(Utc::now() - chrono::Duration::hours(1))
-            .await?;
+        let feeds = data.get_feeds_to_sync(Utc::now()).await?;
 
-        if feeds.len() == 0 {
-            tracing::info!("no feeds to sync");
+        if feeds.is_empty() {
+            tracing::debug!("no feeds to sync");
             continue;
         }
 
         tracing::info!("syncing {} feeds", feeds.len());
 
-        stream::iter(feeds)
+        // feeds from the same site wait on each other, so they're spread out
+        stream::iter(interleave_hosts(feeds, |feed| &feed.feed_url))
             .for_each_concurrent(MAX_SYNCING_FEEDS, |feed| {
                 let data = data.clone();
                 async move {
-                    sync_feed(
-                        &data,
-                        feed.feed_url,
-                        feed.http_etag,
-                        feed.http_last_modified,
-                    )
-                    .await;
+                    sync_feed(&data, feed, &bounds).await;
                 }
             })
             .await;
     }
 }
 
-#[tracing::instrument(name = "sync_feed", skip(data, etag, last_modified))]
-async fn sync_feed(data: &Data, url: String, etag: Option<String>, last_modified: Option<String>) {
-    let result = load_feed(&url, etag, last_modified).await;
+#[tracing::instrument(name = "sync_feed", skip_all, fields(url = feed.feed_url))]
+async fn sync_feed(data: &Data, feed: FeedToSync, bounds: &SyncBounds) {
+    let mut url = feed.feed_url.clone();
+    let result = load_feed(
+        &url,
+        feed.http_etag.clone(),
+        feed.http_last_modified.clone(),
+        LoadOptions {
+            load_icon: icon_needs_refresh(feed.icon_refreshed_at),
+            ignore_robots: feed.ignore_robots,
+        },
+    )
+    .await;
+    let next = next_sync(&feed, &result, bounds);
 
     match result {
-        Ok(FeedResult::Loaded(loaded_feed)) => {
+        Ok(FeedResult::Loaded(mut loaded_feed)) => {
+            url = follow_loaded_feed_move(data, &url, &mut loaded_feed).await;
+
             // Store the new cache headers
             if let Err(e) = data
                 .update_feed_headers(
@@ -86,17 +123,20 @@ async fn sync_feed(data: &Data, url: String, etag: Option<String>, last_modified
 
             if upsert_result.is_err() {
                 set_sync_result(data, &url, SYNC_RESULT_DB_ERROR).await;
+            } else if feed.fetch_full_content {
+                extract_full_content(data, &feed.id, feed.ignore_robots).await;
             }
 
             tracing::info!("feed synced");
         }
-        Ok(FeedResult::NotModified) => {
+        Ok(FeedResult::NotModified { moved_to }) => {
             tracing::info!("feed not modified, skipping");
+            url = follow_feed_move(data, &url, moved_to.as_deref()).await;
             set_sync_result(data, &url, SYNC_RESULT_NOT_MODIFIED).await;
         }
         Ok(result) => {
             match result {
-                FeedResult::Loaded(_) | FeedResult::NotModified => {}
+                FeedResult::Loaded(_) | FeedResult::NotModified { .. } => {}
                 _ => tracing::warn!("unexpected result syncing feed: {result:?}"),
             }
             set_sync_result(data, &url, sync_result_for_feed_result(&result)).await;
@@ -106,6 +146,151 @@ async fn sync_feed(data: &Data, url: String, etag: Option<String>, last_modified
             set_sync_result(data, &url, sync_result_for_error(&err)).await;
         }
     };
+
+    tracing::debug!(?next.delay, "scheduling next sync");
+    let _ = data
+        .schedule_feed_sync(&url, next.interval, next.delay)
+        .await
+        .map_err(|e| tracing::error!("error scheduling next sync: {e:#}"));
+}
+
+/// [`follow_feed_move`] for a feed that was loaded, pointing `loaded_feed` at
+/// the new URL.
+pub async fn follow_loaded_feed_move(
+    data: &Data,
+    feed_url: &str,
+    loaded_feed: &mut LoadedFeed,
+) -> String {
+    // a feed discovered from a page that replaced the feed isn't the same one
+    if loaded_feed.feed.feed_url != feed_url {
+        return feed_url.to_owned();
+    }
+    let url = follow_feed_move(data, feed_url, loaded_feed.moved_to.as_deref()).await;
+    loaded_feed.feed.feed_url = url.clone();
+    url
+}
+
+/// Moves the feed once it has permanently redirected to the same place for a
+/// few syncs, whether or not it changed since. Returns the URL the feed is
+/// stored under.
+pub async fn follow_feed_move(data: &Data, feed_url: &str, moved_to: Option<&str>) -> String {
+    let redirects = match data.record_feed_redirect(feed_url, moved_to).await {
+        Ok(redirects) => redirects,
+        Err(e) => {
+            tracing::error!("error recording feed redirect: {e:#}");
+            return feed_url.to_owned();
+        }
+    };
+    let Some(moved_to) = moved_to else {
+        return feed_url.to_owned();
+    };
+    if redirects < REDIRECTS_BEFORE_MOVE {
+        tracing::info!(moved_to, redirects, "feed redirected permanently");
+        return feed_url.to_owned();
+    }
+
+    match data.move_feed(feed_url, moved_to).await {
+        Ok(true) => {
+            tracing::info!(moved_to, "feed moved");
+            moved_to.to_owned()
+        }
+        Ok(false) => {
+            tracing::warn!(moved_to, "feed moved to a url that's already subscribed to");
+            feed_url.to_owned()
+        }
+        Err(e) => {
+            tracing::error!("error moving feed: {e:#}");
+            feed_url.to_owned()
+        }
+    }
+}
+
+pub struct NextSync {
+    /// How often the feed is synced while it works, kept for the syncs that
+    /// don't load it.
+    pub interval: Duration,
+    /// How long until the next sync, which is longer while the feed fails.
+    pub delay: Duration,
+}
+
+/// When to sync the feed again after `result`. Each failure in a row doubles
+/// the wait, and a `Retry-After` the server sent is honoured.
+pub fn next_sync(
+    feed: &FeedToSync,
+    result: &Result<FeedResult, FeedError>,
+    bounds: &SyncBounds,
+) -> NextSync {
+    let last_interval = bounds.clamp(
+        feed.sync_interval_seconds
+            .and_then(|seconds| u64::try_from(seconds).ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_SYNC_INTERVAL),
+    );
+    // counting the failure that's about to be recorded
+    let failures = u32::try_from(feed.consecutive_failures).unwrap_or(0) + 1;
+
+    match result {
+        Ok(FeedResult::Loaded(loaded_feed)) => {
+            let interval = loaded_feed.sync_interval(bounds);
+            NextSync {
+                interval,
+                delay: interval,
+            }
+        }
+        Ok(FeedResult::NotModified { .. }) => NextSync {
+            interval: last_interval,
+            delay: last_interval,
+        },
+        Ok(_) => NextSync {
+            interval: last_interval,
+            delay: backoff_interval(last_interval, failures, None),
+        },
+        Err(err) => NextSync {
+            interval: last_interval,
+            delay: backoff_interval(last_interval, failures, err.retry_after()),
+        },
+    }
+}
+
+/// Extracts the full content of the feed's recent entries that haven't been
+/// attempted yet. Failures are recorded on the entry rather than failing the
+/// sync. `ignore_robots` follows the feed's override.
+pub async fn extract_full_content(data: &Data, feed_id: &str, ignore_robots: bool) {
+    let entries = match data
+        .get_entries_to_extract(
+            feed_id,
+            Utc::now() - MAX_EXTRACTION_AGE,
+            MAX_EXTRACTIONS_PER_SYNC,
+        )
+        .await
+    {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::error!("error getting entries to extract: {e:#}");
+            return;
+        }
+    };
+
+    if entries.is_empty() {
+        return;
+    }
+    tracing::info!("extracting full content of {} entries", entries.len());
+
+    stream::iter(entries)
+        .for_each_concurrent(MAX_CONCURRENT_EXTRACTIONS, |entry| async move {
+            let result = load_full_content(&entry.url, ignore_robots)
+                .await
+                .map_err(|err| {
+                    tracing::warn!(url = entry.url, "error extracting full content: {err}");
+                    err.to_string()
+                });
+
+            let _ = data
+                .set_entry_extraction(&entry.id, result.as_deref().map_err(String::as_str))
+                .await
+                .map_err(|e| tracing::error!("error storing extracted content: {e:#}"));
+        })
+        .await;
 }
 
 async fn set_sync_result(data: &Data, url: &str, result: &str) {
@@ -0,0 +1,186 @@
+use html5ever::{ParseOpts, parse_fragment, tendril::TendrilSink};
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+
+use crate::db::NewEntry;
+
+/// Titles generated from the body are cut at a word boundary around this
+/// many characters.
+const MAX_TITLE_CHARS: usize = 80;
+
+/// Gives untitled items (microblog and status-style posts) a title made from
+/// their summary or content, or their publish date, so they aren't dropped.
+pub fn with_fallback_title(mut entry: NewEntry) -> NewEntry {
+    if entry.title.trim().is_empty() {
+        entry.title = fallback_title(&entry);
+    }
+    entry
+}
+
+fn fallback_title(entry: &NewEntry) -> String {
+    let bodies = [
+        (&entry.summary, &entry.summary_type),
+        (&entry.content, &entry.content_type),
+    ];
+
+    bodies
+        .into_iter()
+        .filter_map(|(body, body_type)| {
+            let body = body.as_deref()?;
+            let text = match body_type.as_deref() {
+                Some("text" | "text/plain") => body.to_owned(),
+                _ => html_to_text(body),
+            };
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            (!text.is_empty()).then(|| truncate(&text))
+        })
+        .next()
+        .or_else(|| {
+            entry
+                .published_at
+                .map(|date| format!("Post from {}", date.format("%Y-%m-%d %H:%M UTC")))
+        })
+        .unwrap_or_else(|| "Untitled".to_string())
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_TITLE_CHARS {
+        return text.to_owned();
+    }
+
+    let cut: String = text.chars().take(MAX_TITLE_CHARS).collect();
+    let cut = match cut.rsplit_once(' ') {
+        Some((words, _)) if !words.is_empty() => words,
+        _ => &cut,
+    };
+    format!(
+        "{}…",
+        cut.trim_end_matches(|c: char| c.is_ascii_punctuation())
+    )
+}
+
+fn html_to_text(html: &str) -> String {
+    let dom = parse_fragment(
+        RcDom::default(),
+        ParseOpts::default(),
+        html5ever::QualName::new(None, html5ever::ns!(html), html5ever::local_name!("body")),
+        Vec::new(),
+        false,
+    )
+    .one(html);
+
+    let mut text = String::new();
+    collect_text(&dom.document, &mut text);
+    text
+}
+
+fn collect_text(node: &Handle, text: &mut String) {
+    match &node.data {
+        NodeData::Text { contents } => text.push_str(&contents.borrow()),
+        NodeData::Element { name, .. }
+            if matches!(name.local.as_ref(), "script" | "style" | "template") =>
+        {
+            return;
+        }
+        _ => {}
+    }
+
+    for child in node.children.borrow().iter() {
+        collect_text(child, text);
+    }
+
+    // keep words in adjacent blocks apart
+    if let NodeData::Element { name, .. } = &node.data
+        && BLOCK_ELEMENTS.contains(&name.local.as_ref())
+    {
+        text.push(' ');
+    }
+}
+
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn keeps_existing_titles() {
+        let entry = with_fallback_title(NewEntry {
+            title: "Hello".to_string(),
+            summary: Some("Something else".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(entry.title, "Hello");
+    }
+
+    #[test]
+    fn uses_text_of_html_body() {
+        let entry = with_fallback_title(NewEntry {
+            content: Some(
+                "<p>Just shipped <b>v2</b>&nbsp;today!</p><p>Notes</p><script>x()</script>"
+                    .to_string(),
+            ),
+            content_type: Some("html".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(entry.title, "Just shipped v2 today! Notes");
+    }
+
+    #[test]
+    fn prefers_summary_and_truncates_at_word_boundary() {
+        let entry = with_fallback_title(NewEntry {
+            title: "  ".to_string(),
+            summary: Some("word ".repeat(30)),
+            summary_type: Some("text".to_string()),
+            content: Some("<p>Content</p>".to_string()),
+            ..Default::default()
+        });
+        assert!(entry.title.starts_with("word word"));
+        assert!(entry.title.ends_with("word…"));
+        assert!(entry.title.chars().count() <= MAX_TITLE_CHARS + 1);
+    }
+
+    #[test]
+    fn falls_back_to_date() {
+        let entry = with_fallback_title(NewEntry {
+            content: Some("<img src=\"photo.jpg\">".to_string()),
+            published_at: Some(Utc.with_ymd_and_hms(2026, 1, 5, 9, 30, 0).unwrap()),
+            ..Default::default()
+        });
+        assert_eq!(entry.title, "Post from 2026-01-05 09:30 UTC");
+
+        assert_eq!(with_fallback_title(NewEntry::default()).title, "Untitled");
+    }
+}
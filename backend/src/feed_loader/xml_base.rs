@@ -0,0 +1,154 @@
+use quick_xml::{Reader, events::Event};
+use url::Url;
+
+/// Effective base URLs of a feed document, taking `xml:base` attributes into
+/// account on top of the URL the document was fetched from.
+pub struct XmlBases {
+    feed: Url,
+    items: Vec<Url>,
+}
+
+impl XmlBases {
+    /// Base of the whole document, for formats without `xml:base`.
+    pub fn document(document_url: &Url) -> Self {
+        Self {
+            feed: document_url.to_owned(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Records the base of the `channel`/`feed` element and of every
+    /// `item`/`entry`, in document order. Never fails: whatever can't be read
+    /// or resolved falls back to the enclosing base.
+    pub fn read(bytes: &[u8], document_url: &Url) -> Self {
+        let mut reader = Reader::from_reader(bytes);
+        let mut buf = Vec::new();
+
+        let mut bases = Self::document(document_url);
+        // (local name, base) of the open elements
+        let mut stack: Vec<(Vec<u8>, Url)> = Vec::new();
+
+        loop {
+            let (element, is_empty) = match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(element)) => (element, false),
+                Ok(Event::Empty(element)) => (element, true),
+                Ok(Event::End(_)) => {
+                    stack.pop();
+                    buf.clear();
+                    continue;
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {
+                    buf.clear();
+                    continue;
+                }
+            };
+
+            let parent = stack.last();
+            let parent_base = parent.map_or(document_url, |(_, base)| base);
+            let base = element
+                .attributes()
+                .flatten()
+                .find(|attr| attr.key.as_ref() == b"xml:base")
+                .and_then(|attr| attr.unescape_value().ok())
+                .and_then(|value| parent_base.join(value.trim()).ok())
+                .unwrap_or_else(|| parent_base.to_owned());
+
+            let name = element.local_name().as_ref().to_vec();
+            let parent_name = parent.map(|(name, _)| name.as_slice());
+
+            match (name.as_slice(), parent_name) {
+                (b"channel", _) | (b"feed", None) => bases.feed = base.clone(),
+                (b"item", Some(b"channel" | b"RDF")) | (b"entry", Some(b"feed")) => {
+                    bases.items.push(base.clone())
+                }
+                _ => {}
+            }
+
+            if !is_empty {
+                stack.push((name, base));
+            }
+            buf.clear();
+        }
+
+        bases
+    }
+
+    pub fn feed(&self) -> &Url {
+        &self.feed
+    }
+
+    /// Base of the item at `index`, counting every item in the document.
+    pub fn item(&self, index: usize) -> &Url {
+        self.items.get(index).unwrap_or(&self.feed)
+    }
+}
+
+/// Resolves a possibly relative URL against `base`. Absolute URLs, and URLs
+/// that can't be resolved, are returned trimmed but otherwise as written so
+/// that already stored entry URLs don't change through normalization.
+pub fn resolve(base: &Url, href: &str) -> String {
+    let href = href.trim();
+    if Url::parse(href).is_ok() {
+        return href.to_owned();
+    }
+    match base.join(href) {
+        Ok(url) => url.to_string(),
+        Err(_) => href.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_relative_urls() {
+        let base = Url::parse("https://example.com/blog/posts/index.html").unwrap();
+
+        assert_eq!(resolve(&base, "a"), "https://example.com/blog/posts/a");
+        assert_eq!(resolve(&base, "../a"), "https://example.com/blog/a");
+        assert_eq!(resolve(&base, "/a"), "https://example.com/a");
+        assert_eq!(
+            resolve(&base, "//cdn.example.net/a"),
+            "https://cdn.example.net/a"
+        );
+        assert_eq!(
+            resolve(&base, " HTTPS://Other.example "),
+            "HTTPS://Other.example"
+        );
+        assert_eq!(
+            resolve(&base, "?page=2"),
+            "https://example.com/blog/posts/index.html?page=2"
+        );
+    }
+
+    #[test]
+    fn reads_nested_xml_base() {
+        let document = br#"<?xml version="1.0"?>
+            <feed xmlns="http://www.w3.org/2005/Atom" xml:base="/blog/">
+                <entry xml:base="2026/"><link href="a"/></entry>
+                <entry><link href="b"/></entry>
+                <entry xml:base="https://mirror.example.net/x/"/>
+            </feed>"#;
+        let bases = XmlBases::read(document, &Url::parse("https://example.com/feed").unwrap());
+
+        assert_eq!(bases.feed().as_str(), "https://example.com/blog/");
+        assert_eq!(bases.item(0).as_str(), "https://example.com/blog/2026/");
+        assert_eq!(bases.item(1).as_str(), "https://example.com/blog/");
+        assert_eq!(bases.item(2).as_str(), "https://mirror.example.net/x/");
+        assert_eq!(bases.item(3).as_str(), "https://example.com/blog/");
+    }
+
+    #[test]
+    fn ignores_nested_items() {
+        let document = br#"<rss><channel xml:base="https://example.com/c/">
+                <item><foo><item xml:base="/nested/"/></foo></item>
+                <item xml:base="two/"/>
+            </channel></rss>"#;
+        let bases = XmlBases::read(document, &Url::parse("https://example.com/rss").unwrap());
+
+        assert_eq!(bases.item(0).as_str(), "https://example.com/c/");
+        assert_eq!(bases.item(1).as_str(), "https://example.com/c/two/");
+    }
+}
@@ -20,11 +20,14 @@ pub async fn main() {
         .init();
 
     let config = Config::new().expect("valid config");
+    feed_loader::set_http_options(config.http_options().expect("valid http options"));
+    feed_loader::set_host_limits(config.host_limits().expect("valid host limits"));
 
     let data = db::new_pg_data(&config.database_url)
         .await
         .expect("creating data");
 
+    let sync_bounds = config.sync_bounds().expect("valid sync intervals");
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
     tokio::spawn(async move {
@@ -34,7 +37,7 @@ pub async fn main() {
     });
 
     let _ = tokio::join!(
-        feed_loader::feed_sync_loop(data.clone(), shutdown_rx.clone()),
+        feed_loader::feed_sync_loop(data.clone(), sync_bounds, shutdown_rx.clone()),
         api::start_api(data, config.into(), shutdown_rx)
     );
 